@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet}, io::Cursor, path::{Path, PathBuf}, sync::Arc, time::{Duration, SystemTime}
+    collections::{BTreeSet, HashMap, HashSet}, io::Cursor, path::{Path, PathBuf}, sync::Arc, time::{Duration, SystemTime}
 };
 
 use auth::{
@@ -11,14 +11,14 @@ use auth::{
 };
 use base64::Engine;
 use bridge::{
-    handle::{BackendHandle, BackendReceiver, FrontendHandle}, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{ContentType, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceWorldSummary}, message::{EmbeddedOrRaw, MessageToFrontend}, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+    handle::{BackendHandle, BackendReceiver, FrontendHandle}, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{ContentType, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceWorldSummary}, message::{ApplyPlan, EmbeddedOrRaw, MessageToFrontend, SyncBatchResult, SyncChangeReport, SyncDirection, SyncState}, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::{is_path_pattern, is_safe_path_pattern, SafePath}
 };
 use image::ImageFormat;
 use indexmap::IndexSet;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use reqwest::{StatusCode, redirect::Policy};
 use rustc_hash::{FxHashMap, FxHashSet};
-use schema::{auxiliary::AuxiliaryContentMeta, backend_config::{BackendConfig, SyncTargets}, instance::InstanceConfiguration, loader::Loader, modrinth::ModrinthSideRequirement};
+use schema::{auxiliary::AuxiliaryContentMeta, backend_config::{BackendConfig, LinkStrategy, SyncTargets}, instance::InstanceConfiguration, loader::Loader, modrinth::ModrinthSideRequirement};
 use serde::Deserialize;
 use sha1::{Digest, Sha1};
 use tokio::sync::{mpsc::Receiver, OnceCell};
@@ -93,7 +93,13 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
     let account_info = Persistent::load(directories.accounts_json.clone());
 
     // Load config
-    let config = Persistent::load(directories.config_json.clone());
+    let mut config = load_backend_config(directories.config_json.clone());
+    crate::syncing::set_ignore_patterns(&config.get().sync_ignore_patterns);
+    crate::syncing::set_progress_sender(send.clone());
+    if config.get().watch_sync {
+        let _ = std::fs::create_dir_all(&directories.synced_dir);
+        state_file_watching.watch_filesystem(directories.synced_dir.clone(), WatchTarget::SyncedDir);
+    }
 
     let mut state = BackendState {
         self_handle,
@@ -110,6 +116,8 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
         config: Arc::new(RwLock::new(config)),
         secret_storage: Arc::new(OnceCell::new()),
         head_cache: Default::default(),
+        sync_errors: Default::default(),
+        sync_lock: Default::default(),
     };
 
     log::debug!("Doing initial backend load");
@@ -117,6 +125,13 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
     runtime.block_on(async {
         state.send.send(state.account_info.write().get().create_update_message());
         state.load_all_instances().await;
+        state.reconcile_sync_targets();
+        if let Err(error) = state.check_synced_dir_location() {
+            state.send.send_error(error);
+        }
+        if let Some(checkpoint) = crate::syncing::SyncCheckpoint::load(&state.directories) {
+            state.send.send_warning(format!("Syncing of '{}' was interrupted, it can be resumed from the Syncing page", checkpoint.target));
+        }
     });
 
     runtime.spawn(state.start(recv, watcher_rx));
@@ -124,6 +139,29 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
     std::mem::forget(runtime);
 }
 
+/// Loads config.json, migrating it first if it was written by an older version of the launcher
+fn load_backend_config(path: Arc<Path>) -> Persistent<BackendConfig> {
+    if let Ok(bytes) = std::fs::read(&path)
+        && let Ok(mut value) = serde_json::from_slice::<serde_json::Value>(&bytes)
+        && schema::backend_config::migrate_backend_config(&mut value)
+        && let Ok(migrated) = serde_json::to_vec(&value)
+    {
+        log::info!("Migrated config.json to version {}", schema::backend_config::BACKEND_CONFIG_VERSION);
+        if let Err(error) = crate::write_safe(&path, &migrated) {
+            log::error!("Unable to save migrated config.json: {}", error);
+        }
+    }
+
+    let mut config = Persistent::load(path);
+    // A new config has nothing to migrate, but still needs the version so future migrations know where to start
+    if config.get().version < schema::backend_config::BACKEND_CONFIG_VERSION {
+        config.modify(|config| {
+            config.version = schema::backend_config::BACKEND_CONFIG_VERSION;
+        });
+    }
+    config
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum WatchTarget {
     RootDir,
@@ -135,6 +173,7 @@ pub enum WatchTarget {
     InstanceSavesDir { id: InstanceID },
     ServersDat { id: InstanceID },
     InstanceContentDir { id: InstanceID, folder: ContentFolder },
+    SyncedDir,
 }
 
 pub struct BackendStateInstances {
@@ -166,7 +205,12 @@ pub struct BackendState {
     pub account_info: Arc<RwLock<Persistent<BackendAccountInfo>>>,
     pub config: Arc<RwLock<Persistent<BackendConfig>>>,
     pub secret_storage: Arc<OnceCell<Result<PlatformSecretStorage, SecretStorageError>>>,
-    pub head_cache: Arc<RwLock<FxHashMap<Arc<str>, HeadCacheEntry>>>
+    pub head_cache: Arc<RwLock<FxHashMap<Arc<str>, HeadCacheEntry>>>,
+    /// The last error for each target and instance that failed to apply, shown on the Syncing page
+    pub sync_errors: Arc<RwLock<FxHashMap<(Arc<str>, InstanceID), Arc<str>>>>,
+    /// Held while links or synced copies are being changed, so that enabling a target can't interleave with
+    /// disabling it or with an apply. Taken before config and instance_state
+    pub sync_lock: Arc<Mutex<()>>,
 }
 
 pub enum HeadCacheEntry {
@@ -194,10 +238,53 @@ impl BackendState {
     pub async fn load_all_instances(&mut self) {
         log::info!("Loading all instances");
 
-        let mut paths_with_time = Vec::new();
+        self.file_watching.write().watch_filesystem(self.directories.instances_dir.clone(), WatchTarget::InstancesDir);
+        for path in self.instance_paths_oldest_first() {
+            let success = self.load_instance_from_path(&path, true, false);
+            if !success {
+                self.file_watching.write().watch_filesystem(path.into(), WatchTarget::InvalidInstanceDir);
+            }
+        }
+    }
+
+    /// Re-enumerates the instances folder, for when instances were added or removed without the watcher noticing
+    pub fn rescan_instances(&self) {
+        log::info!("Rescanning instances");
+
+        let removed: Vec<InstanceID> = {
+            let mut instance_state = self.instance_state.write();
+            let mut removed = Vec::new();
+            instance_state.instance_by_path.retain(|path, id| {
+                if path.is_dir() {
+                    true
+                } else {
+                    removed.push(*id);
+                    false
+                }
+            });
+            removed
+        };
+        for id in removed {
+            self.remove_instance(id);
+        }
 
         self.file_watching.write().watch_filesystem(self.directories.instances_dir.clone(), WatchTarget::InstancesDir);
-        for entry in std::fs::read_dir(&self.directories.instances_dir).unwrap() {
+        for path in self.instance_paths_oldest_first() {
+            let success = self.load_instance_from_path(&path, false, false);
+            if !success {
+                self.file_watching.write().watch_filesystem(path.into(), WatchTarget::InvalidInstanceDir);
+            }
+        }
+    }
+
+    fn instance_paths_oldest_first(&self) -> Vec<PathBuf> {
+        let mut paths_with_time = Vec::new();
+
+        let Ok(read_dir) = std::fs::read_dir(&self.directories.instances_dir) else {
+            log::warn!("Unable to read instances folder {:?}", self.directories.instances_dir);
+            return Vec::new();
+        };
+        for entry in read_dir {
             let Ok(entry) = entry else {
                 log::warn!("Error reading directory in instances folder: {:?}", entry.unwrap_err());
                 continue;
@@ -232,26 +319,22 @@ impl BackendState {
         }
 
         paths_with_time.sort_by_key(|(_, time)| *time);
-        for (path, _) in paths_with_time {
-            let success = self.load_instance_from_path(&path, true, false);
-            if !success {
-                self.file_watching.write().watch_filesystem(path.into(), WatchTarget::InvalidInstanceDir);
-            }
-        }
+        paths_with_time.into_iter().map(|(path, _)| path).collect()
     }
 
-    pub fn remove_instance(&mut self, id: InstanceID) {
+    pub fn remove_instance(&self, id: InstanceID) {
         log::info!("Removing instance {id:?}");
 
         let mut instance_state = self.instance_state.write();
 
+        self.sync_errors.write().retain(|(_, instance), _| *instance != id);
         if let Some(instance) = instance_state.instances.remove(id) {
             self.send.send(MessageToFrontend::InstanceRemoved { id });
             self.send.send_info(format!("Instance '{}' removed", instance.name));
         }
     }
 
-    pub fn load_instance_from_path(&mut self, path: &Path, mut show_errors: bool, show_success: bool) -> bool {
+    pub fn load_instance_from_path(&self, path: &Path, mut show_errors: bool, show_success: bool) -> bool {
         let instance = Instance::load_from_folder(&path);
 
         let instance_id = {
@@ -647,22 +730,712 @@ impl BackendState {
     }
 
     pub async fn prelaunch(&self, id: InstanceID, modal_action: &ModalAction) -> Vec<PathBuf> {
-        self.apply_syncing_to_instance(id);
+        let report = self.apply_syncing_to_instance(id, SyncDirection::Auto);
+        if !report.is_empty() {
+            self.send.send_info(describe_sync_changes(&report));
+        }
         self.prelaunch_apply_modpacks(id, modal_action).await
     }
 
-    pub fn apply_syncing_to_instance(&self, id: InstanceID) {
-        let (disable, path) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-            (instance.configuration.get().disable_file_syncing, instance.dot_minecraft_path.clone())
+    /// Returns what changed in each instance by name, leaving out instances where nothing did
+    pub fn apply_syncing_to_all(&self, direction: SyncDirection) -> Vec<(Arc<str>, SyncChangeReport)> {
+        if self.config.write().get().syncing_paused {
+            return Vec::new();
+        }
+        if let Err(error) = self.check_synced_dir_location() {
+            log::warn!("{}", error);
+            return Vec::new();
+        }
+
+        if direction == SyncDirection::Pull {
+            let _sync_lock = self.sync_lock.lock();
+            let (sync_targets, conflict_policy) = {
+                let mut config = self.config.write();
+                let config = config.get();
+                (config.sync_targets.clone(), config.sync_conflict_policy)
+            };
+            let sources = crate::syncing::SyncSources::new(&mut self.instance_state.write(), conflict_policy);
+            crate::syncing::pull_into_store(&sync_targets, &sources, &self.directories);
+        }
+
+        let instances: Vec<(InstanceID, Ustr)> = self.instance_state.read().instances.iter()
+            .map(|instance| (instance.id, instance.name))
+            .collect();
+
+        let mut reports = Vec::new();
+        for (id, name) in instances {
+            let report = self.apply_syncing_to_instance(id, direction);
+            if !report.is_empty() {
+                reports.push((name.as_str().into(), report));
+            }
+        }
+        reports
+    }
+
+    /// Starts watching the synced folder and the .minecraft of running instances for watch_sync. Watching
+    /// is already set up for instances launched afterwards
+    pub fn watch_sync_targets(&self) {
+        _ = std::fs::create_dir_all(&self.directories.synced_dir);
+
+        let mut instance_state = self.instance_state.write();
+        let mut file_watching = self.file_watching.write();
+        file_watching.watch_filesystem(self.directories.synced_dir.clone(), WatchTarget::SyncedDir);
+        for instance in instance_state.instances.iter_mut() {
+            if instance.child.is_some() && !instance.watching_dot_minecraft {
+                instance.watching_dot_minecraft = true;
+                file_watching.watch_filesystem(instance.dot_minecraft_path.clone(), WatchTarget::InstanceDotMinecraftDir {
+                    id: instance.id,
+                });
+            }
+        }
+    }
+
+    /// Re-syncs a file target after it changed inside the synced folder (`owner` is None) or inside the .minecraft
+    /// of a running instance. Only top-level files are seen because the folders are watched non-recursively
+    pub fn handle_sync_target_changed(&self, owner: Option<InstanceID>, path: &Path) {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return;
+        };
+        let is_target = {
+            let mut config = self.config.write();
+            let config = config.get();
+            config.watch_sync && crate::syncing::is_file_target(name, &config.sync_targets)
+        };
+        // Folder targets are links into the store, the changes inside them are already shared
+        if !is_target || crate::syncing::is_link_into_store(path, &self.directories) {
+            return;
+        }
+
+        let ids: Vec<InstanceID> = {
+            let instance_state = self.instance_state.read();
+            match owner {
+                // Changes made while the game isn't running are picked up at the next launch anyway
+                Some(id) => instance_state.instances.get(id).filter(|instance| instance.child.is_some()).map(|instance| instance.id).into_iter().collect(),
+                // Running instances are left alone, the game would overwrite the files on exit
+                None => instance_state.instances.iter().filter(|instance| instance.child.is_none()).map(|instance| instance.id).collect(),
+            }
+        };
+
+        // Writing the merged files triggers this again, but nothing changes the second time so it settles
+        let _sync_lock = self.sync_lock.lock();
+        let mut report = SyncChangeReport::default();
+        for id in ids {
+            let Some(context) = self.instance_sync_context(id) else {
+                continue;
+            };
+            if context.file_syncing_disabled {
+                continue;
+            }
+            let instance_report = crate::syncing::apply_files_to_instance(&context.sync_targets, SyncDirection::Auto, context.link_strategy, context.copy_fallback,
+                &context.sources, &self.directories, &context.dot_minecraft);
+            // Folder targets weren't applied, so their errors from the last launch still stand
+            self.record_sync_errors(id, &instance_report, false);
+            crate::sync_log::record(&self.directories, &context.name, &instance_report);
+            report.append(instance_report);
+        }
+
+        if !report.is_empty() {
+            log::info!("Re-synced after {:?} changed: {}", path, describe_sync_changes(&report));
+        }
+    }
+
+    /// Remembers the errors in `report` so get_sync_state can show them, optionally forgetting the previous errors for the instance
+    fn record_sync_errors(&self, id: InstanceID, report: &SyncChangeReport, replace_previous: bool) {
+        let mut sync_errors = self.sync_errors.write();
+        if replace_previous {
+            sync_errors.retain(|(_, instance), _| *instance != id);
+        }
+        for error in &report.errors {
+            sync_errors.insert((error.target.clone(), id), error.message.clone());
+        }
+    }
+
+    /// Fills in the last error of each target from record_sync_errors
+    pub fn add_sync_errors(&self, state: &mut SyncState) {
+        for ((target, _), message) in self.sync_errors.read().iter() {
+            if let Some(target_state) = state.targets.get_mut(target) {
+                target_state.last_error = Some(message.clone());
+            }
+        }
+    }
+
+    pub fn apply_syncing_to_instance(&self, id: InstanceID, direction: SyncDirection) -> SyncChangeReport {
+        let _sync_lock = self.sync_lock.lock();
+        let Some(context) = self.instance_sync_context(id) else {
+            return SyncChangeReport::default();
+        };
+
+        let report = crate::syncing::apply_to_instance(&context.sync_targets, direction, context.link_strategy, context.copy_fallback,
+            &context.sources, &self.directories, context.dot_minecraft);
+        self.record_sync_errors(id, &report, true);
+        crate::sync_log::record(&self.directories, &context.name, &report);
+        if context.file_syncing_disabled {
+            return report;
+        }
+
+        let mut config = self.config.write();
+        crate::syncing::create_missing_manifests(config.get(), &self.directories);
+        let settings = config.get().synced_instance_settings;
+        drop(config);
+
+        if !settings.is_empty() && let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            instance.configuration.modify(|configuration| {
+                let group = configuration.sync_group.clone();
+                crate::syncing::apply_synced_instance_settings(settings, group.as_deref(), &self.directories, configuration);
+            });
+        }
+
+        report
+    }
+
+    /// What apply_syncing_to_all would do to each instance. Instances with nothing to do are left out.
+    /// Pulling also copies the newest instance files into the store first, which isn't part of the plans
+    pub fn preview_syncing(&self, direction: SyncDirection) -> Vec<(Arc<str>, ApplyPlan)> {
+        let instances: Vec<(InstanceID, Ustr)> = self.instance_state.read().instances.iter()
+            .map(|instance| (instance.id, instance.name))
+            .collect();
+
+        let mut plans = Vec::new();
+        for (id, name) in instances {
+            let Some(context) = self.instance_sync_context(id) else {
+                continue;
+            };
+            let plan = crate::syncing::plan_apply_to_instance(&context.sync_targets, direction, &context.sources,
+                &self.directories, &context.dot_minecraft);
+            if !plan.is_empty() {
+                plans.push((name.as_str().into(), plan));
+            }
+        }
+        plans
+    }
+
+    /// Syncing is refused while the synced folder overlaps an instance, see check_synced_dir_location
+    pub fn check_synced_dir_location(&self) -> Result<(), String> {
+        crate::syncing::check_synced_dir_location(&self.directories, &self.instance_state.read())
+            .map_err(|error| format!("Syncing is disabled: {}", error))
+    }
+
+    /// Returns None if the instance doesn't exist, syncing is paused or the synced folder is in the wrong place
+    fn instance_sync_context(&self, id: InstanceID) -> Option<InstanceSyncContext> {
+        // The game still needs its .minecraft when nothing is synced into it
+        let location_error = self.check_synced_dir_location().err();
+        if location_error.is_some() || self.config.write().get().syncing_paused {
+            if let Some(error) = location_error {
+                log::warn!("{}", error);
+            }
+            if let Some(instance) = self.instance_state.read().instances.get(id) {
+                _ = std::fs::create_dir_all(&instance.dot_minecraft_path);
+            }
+            return None;
+        }
+
+        let (conflict_policy, preserved_option_keys) = {
+            let mut config = self.config.write();
+            let config = config.get();
+            (config.sync_conflict_policy, config.preserved_option_keys.clone())
+        };
+        let mut instance_state = self.instance_state.write();
+        let mut sources = crate::syncing::SyncSources::new(&mut instance_state, conflict_policy);
+        sources.preserved_option_keys = preserved_option_keys;
+        let instance = instance_state.instances.get_mut(id)?;
+        let name = instance.name;
+        let dot_minecraft = instance.dot_minecraft_path.clone();
+        let configuration = instance.configuration.get().clone();
+        drop(instance_state);
+
+        if configuration.disable_file_syncing {
+            return Some(InstanceSyncContext {
+                name,
+                sync_targets: SyncTargets::default(),
+                link_strategy: LinkStrategy::default(),
+                copy_fallback: false,
+                sources,
+                dot_minecraft,
+                file_syncing_disabled: true,
+            });
+        }
+
+        let mut config = self.config.write();
+        let config = config.get();
+        let mut sync_targets = config.sync_targets.clone();
+        for excluded in &configuration.sync_exclusions {
+            sync_targets.files.remove(excluded);
+            sync_targets.folders.remove(excluded);
+        }
+        for (target, filter) in &config.sync_target_filters {
+            if !crate::syncing::instance_matches_filter(filter, &name, &configuration) {
+                sync_targets.files.remove(target);
+                sync_targets.folders.remove(target);
+            }
+        }
+
+        Some(InstanceSyncContext {
+            name,
+            sync_targets,
+            link_strategy: config.link_strategy,
+            copy_fallback: config.link_copy_fallback,
+            sources,
+            dot_minecraft,
+            file_syncing_disabled: false,
+        })
+    }
+
+    pub fn set_syncing(&self, checkpoint: crate::syncing::SyncCheckpoint) {
+        if let Err(error) = self.try_set_syncing(checkpoint, None) {
+            self.send.send_error(error);
+        }
+    }
+
+    /// Enables or disables a target, returning the reason when it couldn't be changed instead of showing it
+    /// `dot_minecraft_dirs` is what list_dot_minecraft_dirs returned when disabling several targets at once
+    fn try_set_syncing(&self, mut checkpoint: crate::syncing::SyncCheckpoint, dot_minecraft_dirs: Option<&[PathBuf]>) -> Result<(), String> {
+        let _sync_lock = self.sync_lock.lock();
+        let target = checkpoint.target.clone();
+        let is_file = checkpoint.is_file;
+        let value = checkpoint.value;
+
+        // Toggling a target starts it over, any earlier errors are for links and copies that no longer exist
+        self.sync_errors.write().retain(|(error_target, _), _| *error_target != target);
+
+        if let Err(error) = self.check_synced_dir_location() {
+            checkpoint.finish();
+            return Err(error);
+        }
+
+        // Targets that are already enabled can always be disabled again, even if they wouldn't pass today
+        if value && let Err(error) = bridge::safe_path::validate_sync_target(&target, is_file) {
+            checkpoint.finish();
+            return Err(format!("Unable to enable syncing of '{}': {}", target, error));
+        }
+
+        let mut write = self.config.write();
+
+        if value {
+            let sync_targets = &write.get().sync_targets;
+            let (other, other_kind) = if is_file { (&sync_targets.folders, "folder") } else { (&sync_targets.files, "file") };
+            if other.contains(&target) {
+                checkpoint.finish();
+                return Err(format!("Unable to enable syncing: '{}' is already being synced as a {}", target, other_kind));
+            }
+        }
+
+        if value && crate::syncing::is_case_insensitive(&self.directories)
+            && let Some(other) = crate::syncing::find_case_variant(&target, &write.get().sync_targets)
+        {
+            checkpoint.finish();
+            return Err(format!("Unable to enable syncing: '{}' and '{}' only differ by case, which this filesystem treats as the same name", target, other));
+        }
+
+        if value && !is_file && let Some(alias) = crate::syncing::find_store_alias(&target, &write.get().sync_targets, &self.directories) {
+            checkpoint.finish();
+            return Err(format!("Unable to enable syncing: '{}' uses the same synced folder as '{}'", target, alias));
+        }
+
+        let config = write.get();
+        let filter = config.sync_target_filters.get(&target).cloned();
+        let link_strategy = config.link_strategy;
+        let copy_fallback = config.link_copy_fallback;
+        let verified = config.verified_sync_targets.contains(&target);
+        // Moving folders around can take a while, the sync lock already keeps other changes to the targets out
+        drop(write);
+
+        let result = if value {
+            crate::syncing::enable_all(&target, is_file, filter.as_deref(), link_strategy, copy_fallback, &mut checkpoint, &mut *self.instance_state.write(), &self.directories)
+        } else if let Some(dot_minecraft_dirs) = dot_minecraft_dirs {
+            crate::syncing::disable_all_in(&target, is_file, dot_minecraft_dirs, &mut checkpoint, &self.directories).map(|_| true)
+        } else {
+            crate::syncing::disable_all(&target, is_file, &mut checkpoint, &self.directories).map(|_| true)
+        };
+
+        match result {
+            Ok(success) => {
+                if !success {
+                    checkpoint.finish();
+                    return Err(format!("Unable to enable syncing: some instances already have their own '{}' folder", target));
+                }
+            },
+            Err(error) => {
+                checkpoint.finish();
+                return Err(format!("Error while enabling syncing: {error}"));
+            },
+        }
+
+        if value && !is_file && verified {
+            crate::syncing::update_manifest(&target, &self.directories);
+        }
+
+        self.config.write().modify(|config| {
+            let (set, other_set) = if is_file {
+                (&mut config.sync_targets.files, &mut config.sync_targets.folders)
+            } else {
+                (&mut config.sync_targets.folders, &mut config.sync_targets.files)
+            };
+
+            other_set.remove(&target);
+            if value {
+                _ = set.insert(target);
+            } else {
+                set.remove(&target);
+            }
+        });
+
+        let conflicts = checkpoint.conflicts();
+        if !conflicts.is_empty() {
+            for conflict in conflicts {
+                log::warn!("Kept conflicting file while merging '{}': {}", target, conflict);
+            }
+
+            let mut listed = conflicts.iter().take(10).map(|conflict| &**conflict).collect::<Vec<_>>().join(", ");
+            if conflicts.len() > 10 {
+                listed.push_str(&format!(" and {} more", conflicts.len() - 10));
+            }
+            self.send.send_warning(format!("{} file(s) already existed in the synced '{}' folder with different contents. The instance copies were kept with a .conflict suffix: {}",
+                conflicts.len(), target, listed));
+        }
+
+        checkpoint.finish();
+        Ok(())
+    }
+
+    pub fn set_syncing_batch(&self, targets: Vec<(Arc<str>, bool)>, value: bool) -> SyncBatchResult {
+        let mut result = SyncBatchResult::default();
+        let dot_minecraft_dirs = if value {
+            None
         } else {
+            crate::syncing::list_dot_minecraft_dirs(&self.directories).ok()
+        };
+        for (target, is_file) in targets {
+            let checkpoint = crate::syncing::SyncCheckpoint::begin(target.clone(), is_file, value, false, &self.directories);
+            match self.try_set_syncing(checkpoint, dot_minecraft_dirs.as_deref()) {
+                Ok(()) => result.succeeded.push(target),
+                Err(error) => result.failed.push((target, error.into())),
+            }
+        }
+
+        if !result.failed.is_empty() {
+            let total = result.succeeded.len() + result.failed.len();
+            let reasons = result.failed.iter().map(|(_, error)| &**error).collect::<Vec<_>>().join("\n");
+            let action = if value { "enable" } else { "disable" };
+            self.send.send_error(format!("Unable to {} {} of {} sync target(s):\n{}", action, result.failed.len(), total, reasons));
+        }
+        result
+    }
+
+    pub fn rename_sync_target(&self, old: Arc<str>, new: Arc<str>, is_file: bool) {
+        if old == new {
+            return;
+        }
+        if let Err(error) = bridge::safe_path::validate_sync_target(&new, is_file) {
+            self.send.send_error(format!("Unable to rename '{}' to '{}': {}", old, new, error));
+            return;
+        }
+        if let Err(error) = self.check_synced_dir_location() {
+            self.send.send_error(error);
+            return;
+        }
+
+        let sync_lock = self.sync_lock.lock();
+        let mut write = self.config.write();
+        let sync_targets = &write.get().sync_targets;
+        let set = if is_file { &sync_targets.files } else { &sync_targets.folders };
+        if !set.contains(&old) {
+            self.send.send_error(format!("'{}' isn't being synced", old));
+            return;
+        }
+        if sync_targets.files.contains(&new) || sync_targets.folders.contains(&new) {
+            self.send.send_error(format!("Unable to rename '{}': '{}' is already being synced", old, new));
+            return;
+        }
+        if crate::syncing::is_case_insensitive(&self.directories)
+            && let Some(other) = crate::syncing::find_case_variant(&new, sync_targets).filter(|other| *other != old)
+        {
+            self.send.send_error(format!("Unable to rename '{}': '{}' and '{}' only differ by case, which this filesystem treats as the same name", old, new, other));
+            return;
+        }
+
+        let link_strategy = write.get().link_strategy;
+        let copy_fallback = write.get().link_copy_fallback;
+        // Moving the synced folder and relinking every instance can take a while, the sync lock already keeps
+        // other changes to the targets out
+        drop(write);
+
+        let result = crate::syncing::rename_target(&old, &new, is_file, link_strategy, copy_fallback, &mut self.instance_state.write(), &self.directories);
+        if let Err(error) = result {
+            self.send.send_error(format!("Unable to rename '{}' to '{}': {}", old, new, error));
+            return;
+        }
+
+        let mut write = self.config.write();
+        write.modify(|config| {
+            let set = if is_file { &mut config.sync_targets.files } else { &mut config.sync_targets.folders };
+            set.remove(&old);
+            set.insert(new.clone());
+            crate::syncing::move_target_settings(config, &old, &new);
+        });
+        drop(write);
+
+        self.sync_errors.write().retain(|(error_target, _), _| *error_target != old);
+
+        let mut instance_state = self.instance_state.write();
+        for instance in instance_state.instances.iter_mut() {
+            if instance.configuration.get().sync_exclusions.contains(&old) {
+                instance.configuration.modify(|configuration| {
+                    configuration.sync_exclusions.remove(&old);
+                    configuration.sync_exclusions.insert(new.clone());
+                });
+            }
+        }
+        drop(instance_state);
+        drop(sync_lock);
+
+        if is_file {
+            self.apply_syncing_to_all(SyncDirection::Auto);
+        }
+        self.send.send_success(format!("Renamed sync target '{}' to '{}'", old, new));
+    }
+
+    pub fn push_from_instance(&self, id: InstanceID, target: Arc<str>) {
+        let (is_file, link_strategy, copy_fallback) = {
+            let mut config = self.config.write();
+            let config = config.get();
+            let is_file = if config.sync_targets.files.contains(&target) {
+                true
+            } else if config.sync_targets.folders.contains(&target) {
+                false
+            } else {
+                self.send.send_error(format!("'{}' isn't being synced", target));
+                return;
+            };
+            (is_file, config.link_strategy, config.link_copy_fallback)
+        };
+
+        if let Err(error) = self.check_synced_dir_location() {
+            self.send.send_error(error);
+            return;
+        }
+
+        let sync_lock = self.sync_lock.lock();
+        let mut instance_state = self.instance_state.write();
+        let Some(instance) = instance_state.instances.get(id) else {
             return;
         };
+        let instance_name = instance.name;
+        let dot_minecraft = instance.dot_minecraft_path.clone();
+
+        let result = crate::syncing::push_from_instance(&target, is_file, &dot_minecraft, link_strategy, copy_fallback, &mut instance_state, &self.directories);
+        drop(instance_state);
+        drop(sync_lock);
 
-        if disable {
-            crate::syncing::apply_to_instance(&SyncTargets::default(), &self.directories, path);
+        if let Err(error) = result {
+            self.send.send_error(format!("Unable to use '{}' from {}: {}", target, instance_name, error));
+            return;
+        }
+
+        if is_file {
+            self.push_file_target(&target);
+        }
+        self.send.send_success(format!("Synced '{}' from {} to every instance", target, instance_name));
+    }
+
+    /// Copies the synced copy of one file target into every instance that syncs it, leaving the other targets alone
+    fn push_file_target(&self, target: &Arc<str>) {
+        let _sync_lock = self.sync_lock.lock();
+        let ids: Vec<InstanceID> = self.instance_state.read().instances.iter().map(|instance| instance.id).collect();
+        for id in ids {
+            let Some(context) = self.instance_sync_context(id) else {
+                continue;
+            };
+            // Exclusions and filters have already been removed from the context's targets
+            if context.file_syncing_disabled || !context.sync_targets.files.contains(target) {
+                continue;
+            }
+
+            let sync_targets = SyncTargets {
+                files: BTreeSet::from([target.clone()]),
+                folders: BTreeSet::new(),
+            };
+            let report = crate::syncing::apply_files_to_instance(&sync_targets, SyncDirection::Push, context.link_strategy, context.copy_fallback,
+                &context.sources, &self.directories, &context.dot_minecraft);
+            self.record_sync_errors(id, &report, false);
+            crate::sync_log::record(&self.directories, &context.name, &report);
+        }
+    }
+
+    pub fn share_instance_settings(&self, id: InstanceID) {
+        let settings = self.config.write().get().synced_instance_settings;
+        if settings.is_empty() {
+            return;
+        }
+
+        let configuration = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let configuration = instance.configuration.get();
+            if configuration.disable_file_syncing {
+                return;
+            }
+            configuration.clone()
         } else {
-            crate::syncing::apply_to_instance(&self.config.write().get().sync_targets, &self.directories, path);
+            return;
+        };
+
+        crate::syncing::store_synced_instance_settings(settings, configuration.sync_group.as_deref(), &self.directories, &configuration);
+    }
+
+    pub fn import_sync_config(&self, contents: &str, replace: bool) {
+        let import: schema::backend_config::SyncConfigExport = match serde_json::from_str(contents) {
+            Ok(import) => import,
+            Err(error) => {
+                self.send.send_error(format!("Unable to read sync configuration: {error}"));
+                return;
+            },
+        };
+
+        let mut skipped = Vec::new();
+        let mut files = BTreeSet::new();
+        for file in import.sync_targets.files {
+            if SafePath::new(&file).is_some() || (is_path_pattern(&file) && is_safe_path_pattern(&file)) {
+                files.insert(file);
+            } else {
+                skipped.push(file);
+            }
+        }
+        let mut folders = BTreeSet::new();
+        for folder in import.sync_targets.folders {
+            // A name can't be synced as a file and a folder at the same time
+            if SafePath::new(&folder).is_some() && !files.contains(&folder) {
+                folders.insert(folder);
+            } else {
+                skipped.push(folder);
+            }
+        }
+
+        let mut config = self.config.write();
+        let current = config.get().sync_targets.clone();
+        // Enabling a target reads its filter and verified state, so these go in first and are put back for the targets
+        // that couldn't be changed
+        let previous_filters = config.get().sync_target_filters.clone();
+        let previous_verified = config.get().verified_sync_targets.clone();
+        config.modify(|config| {
+            let imported = |target: &Arc<str>| files.contains(target) || folders.contains(target);
+            let filters = import.sync_target_filters.into_iter().filter(|(target, _)| imported(target));
+            let verified = import.verified_sync_targets.into_iter().filter(|target| folders.contains(target));
+            if replace {
+                config.sync_target_filters = filters.collect();
+                config.verified_sync_targets = verified.collect();
+                config.synced_instance_settings = import.synced_instance_settings;
+            } else {
+                config.sync_target_filters.extend(filters);
+                config.verified_sync_targets.extend(verified);
+                config.synced_instance_settings |= import.synced_instance_settings;
+            }
+            if replace || import.link_strategy.is_some() {
+                config.link_strategy = import.link_strategy.unwrap_or_default();
+            }
+            if replace || import.sync_conflict_policy.is_some() {
+                config.sync_conflict_policy = import.sync_conflict_policy.unwrap_or_default();
+            }
+        });
+        drop(config);
+
+        let mut changes = Vec::new();
+        if replace {
+            for file in current.files.iter().filter(|file| !files.contains(*file)) {
+                changes.push((file.clone(), true, false));
+            }
+            for folder in current.folders.iter().filter(|folder| !folders.contains(*folder)) {
+                changes.push((folder.clone(), false, false));
+            }
+        }
+        for file in files.iter().filter(|file| !current.files.contains(*file)) {
+            changes.push((file.clone(), true, true));
+        }
+        for folder in folders.iter().filter(|folder| !current.folders.contains(*folder)) {
+            changes.push((folder.clone(), false, true));
+        }
+
+        let (mut added, mut removed) = (0, 0);
+        let mut failed = Vec::new();
+        for (target, is_file, value) in changes {
+            self.set_syncing(crate::syncing::SyncCheckpoint::begin(target.clone(), is_file, value, false, &self.directories));
+
+            let enabled = {
+                let mut config = self.config.write();
+                let sync_targets = &config.get().sync_targets;
+                if is_file { sync_targets.files.contains(&target) } else { sync_targets.folders.contains(&target) }
+            };
+            match (value, enabled) {
+                (true, true) => added += 1,
+                (false, false) => removed += 1,
+                _ => failed.push(target),
+            }
         }
+
+        if !failed.is_empty() {
+            self.config.write().modify(|config| {
+                for target in &failed {
+                    if let Some(filter) = previous_filters.get(target) {
+                        config.sync_target_filters.insert(target.clone(), filter.clone());
+                    } else {
+                        config.sync_target_filters.remove(target);
+                    }
+                    if previous_verified.contains(target) {
+                        config.verified_sync_targets.insert(target.clone());
+                    } else {
+                        config.verified_sync_targets.remove(target);
+                    }
+                }
+            });
+        }
+
+        crate::syncing::create_missing_manifests(self.config.write().get(), &self.directories);
+        self.reconcile_sync_targets();
+
+        self.send.send_success(format!("Imported sync configuration: {} target(s) added, {} removed", added, removed));
+        if !skipped.is_empty() {
+            let skipped = skipped.iter().map(|target| &**target).collect::<Vec<_>>().join(", ");
+            self.send.send_warning(format!("Skipped sync targets that are not safe paths: {skipped}"));
+        }
+    }
+
+    pub fn reconcile_sync_targets(&self) {
+        let mut config = self.config.write();
+        let mut sync_targets = config.get().sync_targets.clone();
+        let merged = crate::syncing::reconcile_sync_targets(&mut sync_targets);
+        if merged.is_empty() {
+            return;
+        }
+        config.modify(|config| {
+            config.sync_targets = sync_targets;
+            for merged in &merged {
+                crate::syncing::move_target_settings(config, &merged.from, &merged.into);
+            }
+        });
+        drop(config);
+
+        let mut instance_state = self.instance_state.write();
+        for instance in instance_state.instances.iter_mut() {
+            let exclusions = &instance.configuration.get().sync_exclusions;
+            if !merged.iter().any(|merged| exclusions.contains(&merged.from)) {
+                continue;
+            }
+            instance.configuration.modify(|configuration| {
+                for merged in &merged {
+                    if configuration.sync_exclusions.remove(&merged.from) {
+                        configuration.sync_exclusions.insert(merged.into.clone());
+                    }
+                }
+            });
+        }
+        drop(instance_state);
+
+        let description = merged.iter().map(|merged| {
+            if merged.is_file && merged.from == merged.into {
+                return format!("file '{}' into the folder with the same name", merged.from);
+            }
+            let kind = if merged.is_file { "file" } else { "folder" };
+            format!("{} '{}' into '{}'", kind, merged.from, merged.into)
+        }).collect::<Vec<_>>().join(", ");
+        log::info!("Merged duplicate sync targets: {}", description);
+        self.send.send_info(format!("Merged duplicate sync targets: {description}"));
     }
 
     pub async fn prelaunch_apply_modpacks(&self, id: InstanceID, modal_action: &ModalAction) -> Vec<PathBuf> {
@@ -1241,3 +2014,37 @@ pub enum LoginError {
     #[error("Cancelled by user")]
     CancelledByUser,
 }
+
+/// The sync targets that apply to an instance once its exclusions and the target filters are taken into account
+struct InstanceSyncContext {
+    name: Ustr,
+    sync_targets: SyncTargets,
+    link_strategy: LinkStrategy,
+    copy_fallback: bool,
+    sources: crate::syncing::SyncSources,
+    dot_minecraft: Arc<Path>,
+    file_syncing_disabled: bool,
+}
+
+fn describe_sync_changes(report: &SyncChangeReport) -> String {
+    let mut changes = Vec::new();
+    if !report.files_copied.is_empty() {
+        changes.push(format!("copied {}", report.files_copied.join(", ")));
+    }
+    if !report.links_created.is_empty() {
+        changes.push(format!("linked {}", report.links_created.join(", ")));
+    }
+    if !report.links_removed.is_empty() {
+        changes.push(format!("unlinked {}", report.links_removed.join(", ")));
+    }
+    if !report.links_repaired.is_empty() {
+        changes.push(format!("repaired {}", report.links_repaired.join(", ")));
+    }
+    if !report.options_changed.is_empty() {
+        changes.push(format!("changed options {}", report.options_changed.join(", ")));
+    }
+    if !report.errors.is_empty() {
+        changes.push(format!("failed {} change(s), see the log for details", report.errors.len()));
+    }
+    format!("Syncing {}", changes.join("; "))
+}
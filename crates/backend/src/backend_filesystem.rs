@@ -163,7 +163,7 @@ impl BackendState {
     async fn filesystem_handle_change(
         &mut self,
         target: WatchTarget,
-        _path: &Arc<Path>,
+        path: &Arc<Path>,
         _after_debounce_effects: &mut AfterDebounceEffects,
     ) -> bool {
         match target {
@@ -171,6 +171,7 @@ impl BackendState {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.mark_servers_dirty();
                 }
+                self.handle_sync_target_changed(Some(id), path);
                 true
             },
             _ => false,
@@ -246,6 +247,9 @@ impl BackendState {
                 }
                 true
             },
+            WatchTarget::SyncedDir => {
+                true
+            },
         }
     }
 
@@ -307,7 +311,9 @@ impl BackendState {
                 if file_name == "instances" {
                     self.load_all_instances().await;
                 } else if file_name == "config.json" {
-                    self.config.write().mark_changed(&path);
+                    let mut config = self.config.write();
+                    config.mark_changed(&path);
+                    crate::syncing::set_ignore_patterns(&config.get().sync_ignore_patterns);
                 } else if file_name == "accounts.json" {
                     let mut account_info = self.account_info.write();
                     account_info.mark_changed(&path);
@@ -388,6 +394,7 @@ impl BackendState {
                         _ => {},
                     }
                 }
+                self.handle_sync_target_changed(Some(id), path);
             },
             WatchTarget::InvalidInstanceDir => {
                 let Some(file_name) = path.file_name() else {
@@ -418,6 +425,9 @@ impl BackendState {
                     }
                 }
             },
+            WatchTarget::SyncedDir => {
+                self.handle_sync_target_changed(None, path);
+            },
         }
     }
 
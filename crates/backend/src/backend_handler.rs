@@ -2,7 +2,7 @@ use std::{io::{BufRead, Read, Seek, SeekFrom, Write}, path::Path, sync::{atomic:
 
 use auth::{credentials::AccountCredentials, models::{MinecraftAccessToken, MinecraftProfileResponse}, secret::PlatformSecretStorage};
 use bridge::{
-    install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{InstanceStatus, ContentType, ContentSummary}, message::{LogFiles, MessageToBackend, MessageToFrontend}, meta::MetadataResult, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, serial::AtomicOptionSerial
+    install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget}, instance::{InstanceStatus, ContentType, ContentSummary}, message::{LogFiles, MessageToBackend, MessageToFrontend, SyncDirection}, meta::MetadataResult, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, serial::AtomicOptionSerial
 };
 use futures::TryFutureExt;
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -13,7 +13,7 @@ use tokio::{io::AsyncBufReadExt, sync::Semaphore};
 use ustr::Ustr;
 
 use crate::{
-    BackendState, LoginError, account::{BackendAccount, MinecraftLoginInfo}, arcfactory::ArcStrFactory, instance::ContentFolder, launch::{ArgumentExpansionKey, LaunchError}, log_reader, metadata::{items::{AssetsIndexMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem, VersionUpdateParameters, VersionV3LoaderFields, VersionV3UpdateParameters}, manager::MetaLoadError}, mod_metadata::ModUpdateAction
+    BackendState, LoginError, account::{BackendAccount, MinecraftLoginInfo}, arcfactory::ArcStrFactory, instance::ContentFolder, launch::{ArgumentExpansionKey, LaunchError}, log_reader, metadata::{items::{AssetsIndexMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem, VersionUpdateParameters, VersionV3LoaderFields, VersionV3UpdateParameters}, manager::MetaLoadError}, mod_metadata::ModUpdateAction, WatchTarget
 };
 
 impl BackendState {
@@ -111,7 +111,27 @@ impl BackendState {
                         configuration.disable_file_syncing = disable_file_syncing;
                     });
                 }
-                self.apply_syncing_to_instance(id);
+                self.apply_syncing_to_instance(id, SyncDirection::Auto);
+            },
+            MessageToBackend::SetInstanceSyncExclusion { id, target, excluded } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        if excluded {
+                            configuration.sync_exclusions.insert(target);
+                        } else {
+                            configuration.sync_exclusions.remove(&target);
+                        }
+                    });
+                }
+                self.apply_syncing_to_instance(id, SyncDirection::Auto);
+            },
+            MessageToBackend::SetInstanceSyncGroup { id, sync_group } => {
+                // Sent while typing, the new group's settings and targets are picked up the next time syncing is applied
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.sync_group = sync_group;
+                    });
+                }
             },
             MessageToBackend::SetInstanceMemory { id, memory } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
@@ -126,6 +146,7 @@ impl BackendState {
                         configuration.wrapper_command = Some(wrapper_command);
                     });
                 }
+                self.share_instance_settings(id);
             },
             MessageToBackend::SetInstanceJvmFlags { id, jvm_flags } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
@@ -133,6 +154,7 @@ impl BackendState {
                         configuration.jvm_flags = Some(jvm_flags);
                     });
                 }
+                self.share_instance_settings(id);
             },
             MessageToBackend::SetInstanceJvmBinary { id, jvm_binary } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
@@ -140,6 +162,7 @@ impl BackendState {
                         configuration.jvm_binary = Some(jvm_binary);
                     });
                 }
+                self.share_instance_settings(id);
             },
             MessageToBackend::SetInstanceLinuxWrapper { id, linux_wrapper } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
@@ -147,6 +170,7 @@ impl BackendState {
                         configuration.linux_wrapper = Some(linux_wrapper);
                     });
                 }
+                self.share_instance_settings(id);
             },
             MessageToBackend::SetInstanceSystemLibraries { id, system_libraries } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
@@ -154,6 +178,7 @@ impl BackendState {
                         configuration.system_libraries = Some(system_libraries);
                     });
                 }
+                self.share_instance_settings(id);
             },
             MessageToBackend::KillInstance { id } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
@@ -244,8 +269,13 @@ impl BackendState {
                         child.stdin.take();
                         child.stdout.take();
 
+                        let watch_sync = self.config.write().get().watch_sync;
                         if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                             instance.child = Some(child);
+                            if watch_sync && !instance.watching_dot_minecraft {
+                                instance.watching_dot_minecraft = true;
+                                self.file_watching.write().watch_filesystem(instance.dot_minecraft_path.clone(), WatchTarget::InstanceDotMinecraftDir { id });
+                            }
                         }
                     },
                     Err(ref err) => {
@@ -812,11 +842,12 @@ impl BackendState {
                 let result = crate::launcher_import::discover_instances_from_other_launchers();
                 _ = channel.send(result);
             },
-            MessageToBackend::GetSyncState { channel } => {
-                let result = crate::syncing::get_sync_state(&self.config.write().get().sync_targets, &mut *self.instance_state.write(), &self.directories);
+            MessageToBackend::GetSyncState { include_details, channel } => {
+                let result = crate::syncing::get_sync_state(self.config.write().get(), include_details, &mut *self.instance_state.write(), &self.directories);
 
                 match result {
-                    Ok(state) => {
+                    Ok(mut state) => {
+                        self.add_sync_errors(&mut state);
                         _ = channel.send(state);
                     },
                     Err(error) => {
@@ -824,43 +855,150 @@ impl BackendState {
                     },
                 }
             },
-            MessageToBackend::SetSyncing { target, is_file, value } => {
-                let mut write = self.config.write();
+            MessageToBackend::PushFromInstance { id, target } => {
+                self.push_from_instance(id, target);
+            },
+            MessageToBackend::ExportSyncConfig { channel } => {
+                let export = schema::backend_config::SyncConfigExport::from_config(self.config.write().get());
+                match serde_json::to_string_pretty(&export) {
+                    Ok(json) => {
+                        _ = channel.send(json);
+                    },
+                    Err(error) => {
+                        self.send.send_error(format!("Error while exporting sync configuration: {error}"));
+                    },
+                }
+            },
+            MessageToBackend::ImportSyncConfig { contents, replace } => {
+                self.import_sync_config(&contents, replace);
+            },
+            MessageToBackend::RescanInstances { channel } => {
+                self.rescan_instances();
 
-                let result = if value {
-                    crate::syncing::enable_all(&target, is_file, &mut *self.instance_state.write(), &self.directories)
-                } else {
-                    crate::syncing::disable_all(&target, is_file, &self.directories).map(|_| true)
-                };
+                let result = crate::syncing::get_sync_state(self.config.write().get(), false, &mut *self.instance_state.write(), &self.directories);
 
                 match result {
-                    Ok(success) => {
-                        if !success {
-                            self.send.send_error("Unable to enable syncing");
-                            return;
-                        }
+                    Ok(mut state) => {
+                        self.add_sync_errors(&mut state);
+                        _ = channel.send(state);
                     },
                     Err(error) => {
-                        self.send.send_error(format!("Error while enabling syncing: {error}"));
-                        return;
+                        self.send.send_error(format!("Error while getting sync state: {error}"));
                     },
                 }
-
-                write.modify(|config| {
-                    let (set, other_set) = if is_file {
-                        (&mut config.sync_targets.files, &mut config.sync_targets.folders)
+            },
+            MessageToBackend::GetSyncConfigSummary { channel } => {
+                let summary = crate::syncing::get_sync_config_summary(self.config.write().get(), &mut *self.instance_state.write());
+                _ = channel.send(summary);
+            },
+            MessageToBackend::SetSyncing { target, is_file, value, merge } => {
+                self.set_syncing(crate::syncing::SyncCheckpoint::begin(target, is_file, value, merge, &self.directories));
+            },
+            MessageToBackend::RenameSyncTarget { old, new, is_file } => {
+                self.rename_sync_target(old, new, is_file);
+            },
+            MessageToBackend::SetSyncTargetFilter { target, filter } => {
+                self.config.write().modify(|config| {
+                    match filter {
+                        Some(filter) if !filter.trim().is_empty() => {
+                            config.sync_target_filters.insert(target, filter.trim().into());
+                        },
+                        _ => {
+                            config.sync_target_filters.remove(&target);
+                        },
+                    }
+                });
+                self.apply_syncing_to_all(SyncDirection::Auto);
+            },
+            MessageToBackend::PreviewSync { direction, channel } => {
+                _ = channel.send(self.preview_syncing(direction));
+            },
+            MessageToBackend::PreviewDisable { target, channel } => {
+                match crate::syncing::preview_disable(&target, &self.directories) {
+                    Ok(preview) => {
+                        _ = channel.send(preview);
+                    },
+                    Err(error) => {
+                        self.send.send_error(format!("Error while previewing disable: {error}"));
+                    },
+                }
+            },
+            MessageToBackend::SetSyncTargetVerified { target, value } => {
+                if value {
+                    crate::syncing::update_manifest(&target, &self.directories);
+                } else {
+                    crate::syncing::remove_manifest(&target, &self.directories);
+                }
+                self.config.write().modify(|config| {
+                    if value {
+                        config.verified_sync_targets.insert(target);
                     } else {
-                        (&mut config.sync_targets.folders, &mut config.sync_targets.files)
-                    };
-
-                    other_set.remove(&target);
+                        config.verified_sync_targets.remove(&target);
+                    }
+                });
+            },
+            MessageToBackend::ApplySyncing { direction } => {
+                self.apply_syncing_to_all(direction);
+            },
+            MessageToBackend::ApplySyncingWithReport { id, channel } => {
+                let reports = if let Some(id) = id {
+                    let name = self.instance_state.read().instances.get(id).map(|instance| instance.name);
+                    let report = self.apply_syncing_to_instance(id, SyncDirection::Auto);
+                    name.filter(|_| !report.is_empty()).map(|name| (name.as_str().into(), report)).into_iter().collect()
+                } else {
+                    self.apply_syncing_to_all(SyncDirection::Auto)
+                };
+                _ = channel.send(reports);
+            },
+            MessageToBackend::SetSyncingBatch { targets, value, channel } => {
+                _ = channel.send(self.set_syncing_batch(targets, value));
+            },
+            MessageToBackend::ResumeSyncOperation => {
+                if let Some(checkpoint) = crate::syncing::SyncCheckpoint::load(&self.directories) {
+                    self.set_syncing(checkpoint);
+                }
+            },
+            MessageToBackend::DiscardSyncOperation => {
+                if let Some(checkpoint) = crate::syncing::SyncCheckpoint::load(&self.directories) {
+                    checkpoint.finish();
+                }
+            },
+            MessageToBackend::SetSyncedInstanceSetting { setting, value } => {
+                self.config.write().modify(|config| {
                     if value {
-                        _ = set.insert(target);
+                        config.synced_instance_settings.insert(setting);
                     } else {
-                        set.remove(&target);
+                        config.synced_instance_settings.remove(setting);
                     }
                 });
             },
+            MessageToBackend::MaintainStore { channel } => {
+                if let Err(error) = self.check_synced_dir_location() {
+                    self.send.send_error(error);
+                    return;
+                }
+                let _sync_lock = self.sync_lock.lock();
+                let (sync_targets, filters, link_strategy, copy_fallback, backup_retention) = {
+                    let mut config = self.config.write();
+                    let config = config.get();
+                    let backup_retention = (config.sync_backup_retention_days > 0)
+                        .then(|| Duration::from_secs(u64::from(config.sync_backup_retention_days) * 24 * 60 * 60));
+                    (config.sync_targets.clone(), config.sync_target_filters.clone(), config.link_strategy, config.link_copy_fallback, backup_retention)
+                };
+                let result = crate::syncing::maintain_store(&sync_targets, &filters, link_strategy, copy_fallback, backup_retention, &mut *self.instance_state.write(), &self.directories);
+
+                match result {
+                    Ok(report) => {
+                        _ = channel.send(report);
+                    },
+                    Err(error) => {
+                        self.send.send_error(format!("Error while tidying up synced folders: {error}"));
+                    },
+                }
+            },
+            MessageToBackend::GetSyncLog { limit, channel } => {
+                _ = channel.send(crate::sync_log::read_recent(&self.directories, limit));
+            },
             MessageToBackend::GetBackendConfiguration { channel } => {
                 let configuration = self.config.write().get().clone();
                 _ = channel.send(configuration);
@@ -1071,6 +1209,38 @@ impl BackendState {
                     config.dont_open_game_output_when_launching = !value;
                 });
             },
+            MessageToBackend::SetSyncingPaused { value } => {
+                // Nothing is applied when resuming, each instance picks the targets back up the next time it launches
+                self.config.write().modify(|config| {
+                    config.syncing_paused = value;
+                });
+            },
+            MessageToBackend::SetLinkCopyFallback { value } => {
+                // Only folders linked from now on are affected, existing links and copies stay as they are
+                self.config.write().modify(|config| {
+                    config.link_copy_fallback = value;
+                });
+            },
+            MessageToBackend::SetWatchSync { value } => {
+                self.config.write().modify(|config| {
+                    config.watch_sync = value;
+                });
+                // Turning it off leaves the watches in place, handle_sync_target_changed ignores them
+                if value {
+                    self.watch_sync_targets();
+                }
+            },
+            MessageToBackend::SetSyncConflictPolicy { policy } => {
+                self.config.write().modify(|config| {
+                    config.sync_conflict_policy = policy;
+                });
+            },
+            MessageToBackend::SetLinkStrategy { strategy } => {
+                // Existing links are left as-is, they are still recognised and only new links use the new strategy
+                self.config.write().modify(|config| {
+                    config.link_strategy = strategy;
+                });
+            },
             MessageToBackend::CreateInstanceShortcut { id, path } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     let Ok(current_exe) = std::env::current_exe() else {
@@ -25,6 +25,7 @@ pub struct LauncherDirectories {
     pub root_launcher_dir: Arc<Path>,
     pub config_json: Arc<Path>,
     pub accounts_json: Arc<Path>,
+    pub sync_log: Arc<Path>,
 }
 
 impl LauncherDirectories {
@@ -54,6 +55,7 @@ impl LauncherDirectories {
 
         let config_json = launcher_dir.join("config.json");
         let accounts_json = launcher_dir.join("accounts.json");
+        let sync_log = launcher_dir.join("sync_log.jsonl");
 
         Self {
             instances_dir: instances_dir.into(),
@@ -80,6 +82,7 @@ impl LauncherDirectories {
             root_launcher_dir: launcher_dir.into(),
             config_json: config_json.into(),
             accounts_json: accounts_json.into(),
+            sync_log: sync_log.into(),
         }
     }
 }
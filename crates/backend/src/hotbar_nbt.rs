@@ -0,0 +1,53 @@
+use std::path::Path;
+
+use anyhow::Context;
+use nbt::NBT;
+
+/// The contents of a hotbar.nbt file, which holds the saved hotbars under the keys "0" to "8".
+/// Every other tag (DataVersion, etc.) is kept as-is
+pub struct HotbarNbt {
+    nbt: NBT,
+}
+
+impl HotbarNbt {
+    pub const ROWS: usize = 9;
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read(path)?;
+
+        let mut nbt_data = raw.as_slice();
+        let nbt = nbt::decode::read_named(&mut nbt_data)?;
+        nbt.as_compound().context("Unable to get root compound")?;
+
+        Ok(Self { nbt })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        nbt::encode::write_named(&self.nbt)
+    }
+
+    /// Whether the saved hotbar holds at least one item. Rows that are missing or aren't a list of items count as empty
+    pub fn has_items(&self, row: usize) -> bool {
+        let root = self.nbt.as_compound().unwrap();
+        let Some(items) = root.find_list(&row.to_string(), nbt::TAG_COMPOUND_ID) else {
+            return false;
+        };
+
+        items.iter()
+            .filter_map(|item| item.as_compound())
+            .any(|item| item.find_string("id").is_some_and(|id| id != "minecraft:air"))
+    }
+
+    /// Replaces a saved hotbar with a copy of the same row from `other`
+    pub fn copy_row_from(&mut self, row: usize, other: &HotbarNbt) {
+        let key = row.to_string();
+        let other_root = other.nbt.as_compound().unwrap();
+        let Some(value) = other_root.find(&key) else {
+            return;
+        };
+
+        let mut root = self.nbt.as_compound_mut().unwrap();
+        root.remove(&key);
+        root.insert_copy(&key, value);
+    }
+}
@@ -19,7 +19,7 @@ use thiserror::Error;
 
 use ustr::Ustr;
 
-use crate::{id_slab::{GetId, Id}, launcher_import, mod_metadata::ModMetadataManager, persistent::Persistent, BackendStateInstances, IoOrSerializationError};
+use crate::{id_slab::{GetId, Id}, launcher_import, mod_metadata::ModMetadataManager, persistent::Persistent, servers_dat::ServersDat, BackendStateInstances, IoOrSerializationError};
 
 #[derive(Debug)]
 pub struct Instance {
@@ -886,41 +886,32 @@ fn load_world_summary(path: &Path) -> anyhow::Result<InstanceWorldSummary> {
 }
 
 fn load_servers_summary(server_dat_path: &Path) -> anyhow::Result<Vec<InstanceServerSummary>> {
-    let raw = std::fs::read(server_dat_path)?;
-
-    let mut nbt_data = raw.as_slice();
-    let result = nbt::decode::read_named(&mut nbt_data)?;
-
-    let root = result.as_compound().context("Unable to get root compound")?;
-    let servers = root.find_list("servers", nbt::TAG_COMPOUND_ID).context("Unable to get servers")?;
+    let servers_dat = ServersDat::read(server_dat_path)?;
+    let servers = servers_dat.servers();
 
     let mut summaries = Vec::with_capacity(servers.len());
 
-    for server in servers.iter() {
-        let server = server.as_compound().unwrap();
-
-        if let Some(hidden) = server.find_byte("hidden")
-            && *hidden != 0
-        {
+    for server in servers {
+        if server.hidden() {
             continue;
         }
 
-        let Some(ip) = server.find_string("ip") else {
+        let Some(ip) = server.ip() else {
             continue;
         };
 
         let name: Arc<str> = server
-            .find_string("name")
-            .map(|v| Arc::from(v.as_str()))
+            .name()
+            .map(Arc::from)
             .unwrap_or_else(|| Arc::from("<unnamed>"));
 
         let icon = server
-            .find_string("icon")
+            .icon()
             .and_then(|v| base64::engine::general_purpose::STANDARD.decode(v).map(Arc::from).ok());
 
         summaries.push(InstanceServerSummary {
             name,
-            ip: Arc::from(ip.as_str()),
+            ip: Arc::from(ip),
             png_icon: icon,
         });
     }
@@ -26,9 +26,13 @@ mod log_reader;
 mod metadata;
 mod mod_metadata;
 mod id_slab;
+mod hotbar_nbt;
 mod persistent;
+mod servers_dat;
 mod shortcut;
 mod syncing;
+mod sync_log;
+pub mod sync_simulation;
 mod update;
 
 pub(crate) fn is_single_component_path(path: &str) -> bool {
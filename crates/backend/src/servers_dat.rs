@@ -0,0 +1,193 @@
+use std::path::Path;
+
+use anyhow::Context;
+use nbt::{CompoundRef, NBT};
+
+/// The contents of a servers.dat file. Every tag is kept as-is so that fields which only
+/// exist in some versions (acceptTextures, previewsChat, etc.) survive a read/write round-trip
+pub struct ServersDat {
+    nbt: NBT,
+}
+
+/// A single server entry, owning a copy of its compound
+pub struct ServerEntry {
+    nbt: NBT,
+}
+
+impl ServersDat {
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read(path)?;
+
+        let mut nbt_data = raw.as_slice();
+        let nbt = nbt::decode::read_named(&mut nbt_data)?;
+        nbt.as_compound().context("Unable to get root compound")?;
+
+        Ok(Self { nbt })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        nbt::encode::write_named(&self.nbt)
+    }
+
+    pub fn servers(&self) -> Vec<ServerEntry> {
+        let root = self.nbt.as_compound().unwrap();
+        let Some(servers) = root.find_list("servers", nbt::TAG_COMPOUND_ID) else {
+            return Vec::new();
+        };
+
+        servers.iter()
+            .filter_map(|server| server.as_compound())
+            .map(|server| ServerEntry { nbt: server.clone_nbt() })
+            .collect()
+    }
+
+    /// Replaces the server list, keeping any other tags in the root compound
+    pub fn set_servers(&mut self, servers: &[ServerEntry]) {
+        let mut root = self.nbt.as_compound_mut().unwrap();
+        root.remove("servers");
+
+        let mut list = root.create_list("servers", nbt::TAG_COMPOUND_ID);
+        for server in servers {
+            server.compound().clone_into(list.create_compound());
+        }
+    }
+}
+
+impl ServerEntry {
+    fn compound(&self) -> CompoundRef<'_> {
+        self.nbt.as_compound().unwrap()
+    }
+
+    pub fn ip(&self) -> Option<String> {
+        self.compound().find_string("ip").cloned()
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.compound().find_string("name").cloned()
+    }
+
+    pub fn icon(&self) -> Option<String> {
+        self.compound().find_string("icon").cloned()
+    }
+
+    pub fn hidden(&self) -> bool {
+        self.compound().find_byte("hidden").is_some_and(|hidden| *hidden != 0)
+    }
+
+    /// The address used to tell whether two entries refer to the same server
+    pub fn key(&self) -> Option<String> {
+        let ip = self.ip()?;
+        let ip = ip.trim();
+        if ip.is_empty() {
+            return None;
+        }
+        Some(ip.to_ascii_lowercase())
+    }
+
+    /// Copies over tags from `other` that this entry doesn't have, so that
+    /// merging two copies of a server doesn't drop preferences only one of them has
+    pub fn fill_missing_from(&mut self, other: &ServerEntry) {
+        let other = other.compound();
+        let mut compound = self.nbt.as_compound_mut().unwrap();
+        for (key, value) in other.entries() {
+            if !compound.contains_key(key) {
+                compound.insert_copy(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nbt::NBT;
+    use rand::RngCore;
+
+    use super::{ServerEntry, ServersDat};
+
+    /// Writes a servers.dat laid out the way the given version saves it. acceptTextures only exists once the player
+    /// answered the resource pack prompt, previewsChat only in 1.19 - 1.19.2 and hidden since 1.20.5
+    fn fixture(ip: &str, name: &str, accept_textures: Option<i8>, previews_chat: Option<i8>, hidden: Option<i8>) -> ServersDat {
+        let mut nbt = NBT::new();
+        let mut root = nbt.as_compound_mut().unwrap();
+        let mut servers = root.create_list("servers", nbt::TAG_COMPOUND_ID);
+        let mut server = servers.create_compound();
+        server.insert_string("ip", ip.into());
+        server.insert_string("name", name.into());
+        server.insert_string("icon", "iVBORw0KGgo=".into());
+        if let Some(hidden) = hidden {
+            server.insert_byte("hidden", hidden);
+        }
+        if let Some(accept_textures) = accept_textures {
+            server.insert_byte("acceptTextures", accept_textures);
+        }
+        if let Some(previews_chat) = previews_chat {
+            server.insert_byte("previewsChat", previews_chat);
+        }
+        ServersDat { nbt }
+    }
+
+    fn minecraft_1_12_2() -> ServersDat {
+        fixture("play.example.com", "Example", Some(1), None, None)
+    }
+
+    fn minecraft_1_19_2() -> ServersDat {
+        fixture("play.example.com", "Example", None, Some(0), None)
+    }
+
+    fn minecraft_1_21_1() -> ServersDat {
+        fixture("PLAY.example.com ", "Example (renamed)", None, None, Some(1))
+    }
+
+    fn round_trip(servers_dat: &ServersDat) -> ServersDat {
+        let path = std::env::temp_dir().join(format!("pandora_servers_dat_{}.dat", rand::thread_rng().next_u64()));
+        std::fs::write(&path, servers_dat.to_bytes()).unwrap();
+        let read = ServersDat::read(&path);
+        _ = std::fs::remove_file(&path);
+        read.unwrap()
+    }
+
+    fn find_byte(server: &ServerEntry, key: &str) -> Option<i8> {
+        server.compound().find_byte(key).copied()
+    }
+
+    #[test]
+    fn round_trip_keeps_version_specific_tags() {
+        for servers_dat in [minecraft_1_12_2(), minecraft_1_19_2(), minecraft_1_21_1()] {
+            let read = round_trip(&servers_dat);
+            assert_eq!(read.nbt, servers_dat.nbt);
+        }
+
+        let servers = round_trip(&minecraft_1_19_2()).servers();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(find_byte(&servers[0], "previewsChat"), Some(0));
+        assert_eq!(find_byte(&servers[0], "acceptTextures"), None);
+    }
+
+    #[test]
+    fn key_ignores_case_and_whitespace() {
+        let old = minecraft_1_12_2().servers();
+        let new = minecraft_1_21_1().servers();
+        assert_eq!(old[0].key(), new[0].key());
+    }
+
+    #[test]
+    fn merging_keeps_preferences_from_older_versions() {
+        let older = minecraft_1_12_2().servers();
+        let mut merged = minecraft_1_19_2().servers();
+        merged[0].fill_missing_from(&older[0]);
+        let mut newest = minecraft_1_21_1().servers();
+        newest[0].fill_missing_from(&merged[0]);
+
+        let server = &newest[0];
+        assert_eq!(server.name().as_deref(), Some("Example (renamed)"));
+        assert_eq!(find_byte(server, "acceptTextures"), Some(1));
+        assert_eq!(find_byte(server, "previewsChat"), Some(0));
+        assert!(server.hidden());
+
+        let mut servers_dat = minecraft_1_21_1();
+        servers_dat.set_servers(&newest);
+        let servers = round_trip(&servers_dat).servers();
+        assert_eq!(find_byte(&servers[0], "acceptTextures"), Some(1));
+        assert_eq!(find_byte(&servers[0], "previewsChat"), Some(0));
+    }
+}
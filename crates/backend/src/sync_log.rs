@@ -0,0 +1,124 @@
+use std::{io::Write, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::message::{SyncChangeReport, SyncLogEntry, SyncLogOperation};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::directories::LauncherDirectories;
+
+/// Once the log grows past this it is moved aside, so only the current and the previous log are kept
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// One line of sync_log.jsonl
+#[derive(Serialize, Deserialize)]
+struct LoggedOperation {
+    time: DateTime<Utc>,
+    instance: Arc<str>,
+    operation: LoggedOperationKind,
+    target: Arc<str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<Arc<str>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum LoggedOperationKind {
+    CopyFile,
+    CreateLink,
+    RemoveLink,
+    RepairLink,
+    ChangeOption,
+    Failed,
+}
+
+impl From<LoggedOperationKind> for SyncLogOperation {
+    fn from(kind: LoggedOperationKind) -> Self {
+        match kind {
+            LoggedOperationKind::CopyFile => SyncLogOperation::CopyFile,
+            LoggedOperationKind::CreateLink => SyncLogOperation::CreateLink,
+            LoggedOperationKind::RemoveLink => SyncLogOperation::RemoveLink,
+            LoggedOperationKind::RepairLink => SyncLogOperation::RepairLink,
+            LoggedOperationKind::ChangeOption => SyncLogOperation::ChangeOption,
+            LoggedOperationKind::Failed => SyncLogOperation::Failed,
+        }
+    }
+}
+
+fn previous_log_path(directories: &LauncherDirectories) -> PathBuf {
+    directories.sync_log.with_extension("old.jsonl")
+}
+
+/// Appends everything `report` changed in `instance` to the sync log. Unlike the regular log this
+/// survives restarts, so it can tell what syncing did to an instance a few launches ago
+pub fn record(directories: &LauncherDirectories, instance: &str, report: &SyncChangeReport) {
+    if report.is_empty() {
+        return;
+    }
+
+    let time = Utc::now();
+    let instance: Arc<str> = instance.into();
+    let entry = |operation, target: &Arc<str>, error: Option<Arc<str>>| LoggedOperation {
+        time,
+        instance: instance.clone(),
+        operation,
+        target: target.clone(),
+        error,
+    };
+
+    let mut entries = Vec::new();
+    entries.extend(report.links_removed.iter().map(|target| entry(LoggedOperationKind::RemoveLink, target, None)));
+    entries.extend(report.links_created.iter().map(|target| entry(LoggedOperationKind::CreateLink, target, None)));
+    entries.extend(report.links_repaired.iter().map(|target| entry(LoggedOperationKind::RepairLink, target, None)));
+    entries.extend(report.files_copied.iter().map(|target| entry(LoggedOperationKind::CopyFile, target, None)));
+    entries.extend(report.options_changed.iter().map(|target| entry(LoggedOperationKind::ChangeOption, target, None)));
+    entries.extend(report.errors.iter().map(|error| entry(LoggedOperationKind::Failed, &error.target, Some(error.message.clone()))));
+
+    let mut lines = Vec::new();
+    for entry in &entries {
+        match serde_json::to_writer(&mut lines, entry) {
+            Ok(()) => lines.push(b'\n'),
+            Err(error) => log::error!("Unable to serialize sync log entry: {error}"),
+        }
+    }
+
+    if let Err(error) = append(&directories.sync_log, &previous_log_path(directories), &lines) {
+        log::error!("Unable to write to the sync log: {error}");
+    }
+}
+
+fn append(path: &Path, previous_path: &Path, lines: &[u8]) -> std::io::Result<()> {
+    if let Ok(metadata) = std::fs::metadata(path) && metadata.len() > MAX_LOG_SIZE {
+        std::fs::rename(path, previous_path)?;
+    }
+
+    let mut file = std::fs::File::options().create(true).append(true).open(path)?;
+    file.write_all(lines)
+}
+
+/// Returns up to `limit` of the most recent entries, newest first. Lines that can't be read are skipped
+pub fn read_recent(directories: &LauncherDirectories, limit: usize) -> Vec<SyncLogEntry> {
+    let mut entries = Vec::new();
+
+    for path in [directories.sync_log.to_path_buf(), previous_log_path(directories)] {
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        for line in contents.lines().rev() {
+            if entries.len() >= limit {
+                return entries;
+            }
+            let Ok(logged) = serde_json::from_str::<LoggedOperation>(line) else {
+                continue;
+            };
+            entries.push(SyncLogEntry {
+                time: logged.time.into(),
+                instance: logged.instance,
+                operation: logged.operation.into(),
+                target: logged.target,
+                error: logged.error,
+            });
+        }
+    }
+
+    entries
+}
@@ -0,0 +1,251 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc, time::{Duration, SystemTime}};
+
+use bridge::{instance::InstanceID, message::{SyncChangeReport, SyncDirection, SyncState}, safe_path::SafePath};
+use rand::RngCore;
+use schema::{backend_config::{BackendConfig, LinkStrategy, SyncConflictPolicy, SyncTargets}, instance::InstanceConfiguration, loader::Loader};
+use serde::{Deserialize, Serialize};
+
+use crate::{directories::LauncherDirectories, id_slab::IdSlab, instance::Instance, syncing::SyncCheckpoint, BackendStateInstances};
+
+/// A synthetic set of instances to run the sync logic against, e.g. to attach a reproducible scenario to a bug report
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncSimulationSpec {
+    #[serde(default)]
+    pub sync_targets: SyncTargets,
+    #[serde(default)]
+    pub link_strategy: LinkStrategy,
+    #[serde(default)]
+    pub conflict_policy: SyncConflictPolicy,
+    /// Files that already exist in the synced folder, relative path to contents
+    #[serde(default)]
+    pub store_files: BTreeMap<String, String>,
+    /// Instances in order of age, files in later instances are treated as more recently modified
+    #[serde(default)]
+    pub instances: Vec<SimulatedInstance>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SimulatedInstance {
+    pub name: String,
+    #[serde(default)]
+    pub disable_file_syncing: bool,
+    /// Files inside .minecraft, relative path to contents
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    /// Empty folders inside .minecraft
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SyncSimulationResult {
+    /// Folder targets that enable_all refused to enable, e.g. because an instance already had a real folder there
+    pub refused_targets: Vec<Arc<str>>,
+    pub reports: BTreeMap<String, SyncChangeReport>,
+    pub state: SyncState,
+    /// Every path in the simulated launcher folder, relative to its root, described as `dir`, `link -> <target>` or the file contents
+    pub tree: BTreeMap<String, String>,
+}
+
+/// Builds the scenario in a temporary folder, enables the folder targets and applies syncing
+/// to every instance the same way launching would, then returns the outcome. Nothing is sent to the frontend
+pub fn simulate_sync(spec: &SyncSimulationSpec) -> std::io::Result<SyncSimulationResult> {
+    let root = std::env::temp_dir().join(format!("pandora_sync_simulation_{}", rand::thread_rng().next_u64()));
+    let result = crate::syncing::without_progress(|| run_simulation(spec, &root));
+    _ = std::fs::remove_dir_all(&root);
+    result
+}
+
+fn run_simulation(spec: &SyncSimulationSpec, root: &Path) -> std::io::Result<SyncSimulationResult> {
+    let directories = LauncherDirectories::new(root.to_path_buf());
+    std::fs::create_dir_all(&directories.synced_dir)?;
+    std::fs::create_dir_all(&directories.instances_dir)?;
+
+    let now = SystemTime::now();
+    for (path, contents) in &spec.store_files {
+        write_simulated_file(&directories.synced_dir, path, contents, now)?;
+    }
+
+    let mut instances = BackendStateInstances {
+        instances: IdSlab::default(),
+        instance_by_path: Default::default(),
+        instances_generation: 0,
+        reload_immediately: Default::default(),
+    };
+
+    for (index, simulated) in spec.instances.iter().enumerate() {
+        if !crate::is_single_component_path(&simulated.name) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid instance name: {}", simulated.name)));
+        }
+
+        let instance_dir = directories.instances_dir.join(&simulated.name);
+        let dot_minecraft = instance_dir.join(".minecraft");
+        std::fs::create_dir_all(&dot_minecraft)?;
+
+        let mut configuration = InstanceConfiguration::new("1.21.1".into(), Loader::Vanilla);
+        configuration.disable_file_syncing = simulated.disable_file_syncing;
+        std::fs::write(instance_dir.join("info_v1.json"), serde_json::to_vec(&configuration)?)?;
+
+        let modified = now + Duration::from_secs(60 * (index as u64 + 1));
+        for (path, contents) in &simulated.files {
+            write_simulated_file(&dot_minecraft, path, contents, modified)?;
+        }
+        for folder in &simulated.folders {
+            let folder = SafePath::new(folder).ok_or_else(|| invalid_path(folder))?;
+            std::fs::create_dir_all(folder.to_path(&dot_minecraft))?;
+        }
+
+        let mut instance = Instance::load_from_folder(&instance_dir).map_err(std::io::Error::other)?;
+        instances.instances.insert(move |index| {
+            instance.id = InstanceID { index, generation: 0 };
+            instance
+        });
+    }
+
+    let mut refused_targets = Vec::new();
+    for folder in &spec.sync_targets.folders {
+        let mut checkpoint = SyncCheckpoint::begin(folder.clone(), false, true, false, &directories);
+        if !crate::syncing::enable_all(folder, false, None, spec.link_strategy, false, &mut checkpoint, &mut instances, &directories)? {
+            refused_targets.push(folder.clone());
+        }
+        checkpoint.finish();
+    }
+
+    let sources = crate::syncing::SyncSources::new(&mut instances, spec.conflict_policy);
+    let mut reports = BTreeMap::new();
+    for instance in instances.instances.iter_mut() {
+        let sync_targets = if instance.configuration.get().disable_file_syncing {
+            SyncTargets::default()
+        } else {
+            spec.sync_targets.clone()
+        };
+        let report = crate::syncing::apply_to_instance(&sync_targets, SyncDirection::Auto, spec.link_strategy, false, &sources, &directories, instance.dot_minecraft_path.clone());
+        reports.insert(instance.name.to_string(), report);
+    }
+
+    let config = BackendConfig {
+        sync_targets: spec.sync_targets.clone(),
+        link_strategy: spec.link_strategy,
+        sync_conflict_policy: spec.conflict_policy,
+        ..Default::default()
+    };
+    let state = crate::syncing::get_sync_state(&config, true, &mut instances, &directories)?;
+
+    Ok(SyncSimulationResult {
+        refused_targets,
+        reports,
+        state,
+        tree: describe_tree(root),
+    })
+}
+
+fn write_simulated_file(base: &Path, path: &str, contents: &str, modified: SystemTime) -> std::io::Result<()> {
+    let path = SafePath::new(path).ok_or_else(|| invalid_path(path))?.to_path(base);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, contents)?;
+    std::fs::File::options().write(true).open(&path)?.set_modified(modified)
+}
+
+fn invalid_path(path: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid path: {}", path))
+}
+
+fn describe_tree(root: &Path) -> BTreeMap<String, String> {
+    let mut tree = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(root).min_depth(1).into_iter().flatten() {
+        let Ok(relative) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+
+        let description = if let Ok(target) = std::fs::read_link(entry.path()) {
+            let target = target.strip_prefix(root).unwrap_or(&target).to_string_lossy().replace('\\', "/");
+            format!("link -> {}", target)
+        } else if entry.file_type().is_dir() {
+            "dir".into()
+        } else {
+            std::fs::read_to_string(entry.path()).unwrap_or_else(|_| "<binary>".into())
+        };
+
+        tree.insert(relative, description);
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(name: &str, files: &[(&str, &str)], folders: &[&str]) -> SimulatedInstance {
+        SimulatedInstance {
+            name: name.into(),
+            disable_file_syncing: false,
+            files: files.iter().map(|(path, contents)| (path.to_string(), contents.to_string())).collect(),
+            folders: folders.iter().map(|folder| folder.to_string()).collect(),
+        }
+    }
+
+    fn spec(folders: &[&str], instances: Vec<SimulatedInstance>) -> SyncSimulationSpec {
+        SyncSimulationSpec {
+            sync_targets: SyncTargets {
+                files: Default::default(),
+                folders: folders.iter().map(|folder| Arc::from(*folder)).collect(),
+            },
+            // Copying works everywhere, symlinks need Developer Mode on Windows
+            link_strategy: LinkStrategy::Copy,
+            instances,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn synced_folder_reaches_every_instance() {
+        let mut spec = spec(&["saves"], vec![instance("a", &[], &[]), instance("b", &[], &[])]);
+        spec.store_files.insert("saves/world/level.dat".into(), "level".into());
+
+        let result = simulate_sync(&spec).unwrap();
+
+        assert!(result.refused_targets.is_empty());
+        for name in ["a", "b"] {
+            let path = format!("instances/{}/.minecraft/saves/world/level.dat", name);
+            assert_eq!(result.tree.get(&path).map(String::as_str), Some("level"));
+            assert!(result.reports[name].errors.is_empty());
+        }
+        let saves = result.state.target("saves", false).unwrap();
+        assert!(saves.enabled);
+        assert_eq!(saves.sync_count, 2);
+    }
+
+    #[test]
+    fn existing_folder_refuses_target() {
+        let spec = spec(&["saves"], vec![instance("a", &[("saves/world/level.dat", "mine")], &[]), instance("b", &[], &[])]);
+
+        let result = simulate_sync(&spec).unwrap();
+
+        assert_eq!(result.refused_targets, vec![Arc::<str>::from("saves")]);
+        assert_eq!(result.tree.get("instances/a/.minecraft/saves/world/level.dat").map(String::as_str), Some("mine"));
+    }
+
+    #[test]
+    fn instance_with_file_syncing_disabled_is_left_alone() {
+        let mut disabled = instance("b", &[], &["saves"]);
+        disabled.disable_file_syncing = true;
+        let mut spec = spec(&["saves"], vec![instance("a", &[], &[]), disabled]);
+        spec.store_files.insert("saves/world/level.dat".into(), "level".into());
+
+        let result = simulate_sync(&spec).unwrap();
+
+        assert!(result.refused_targets.is_empty());
+        assert_eq!(result.tree.get("instances/a/.minecraft/saves/world/level.dat").map(String::as_str), Some("level"));
+        assert_eq!(result.tree.get("instances/b/.minecraft/saves").map(String::as_str), Some("dir"));
+        assert!(!result.tree.contains_key("instances/b/.minecraft/saves/world/level.dat"));
+    }
+
+    #[test]
+    fn invalid_instance_name_is_rejected() {
+        let spec = spec(&[], vec![instance("../escape", &[], &[])]);
+        assert!(simulate_sync(&spec).is_err());
+    }
+}
@@ -1,20 +1,34 @@
-use std::{collections::BTreeMap, path::{Path, PathBuf}, sync::Arc, time::SystemTime};
-
-use bridge::{message::{SyncState, SyncTargetState}, safe_path::SafePath};
-use once_cell::sync::Lazy;
+use std::{cell::{Cell, RefCell}, collections::{BTreeMap, BTreeSet}, path::{Path, PathBuf}, sync::Arc, time::{Duration, Instant, SystemTime}};
+
+use bridge::{handle::FrontendHandle, message::{ApplyPlan, DisablePreview, InterruptedSyncOperation, MessageToFrontend, StoreMaintenanceReport, SyncChangeReport, SyncDirection, SyncError, SyncOperation, SyncState, SyncTargetState}, safe_path::{is_path_pattern, is_safe_path_pattern, SafePath}};
+use enumset::EnumSet;
+use indexmap::IndexMap;
+use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::RwLock;
+use rand::RngCore;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use relative_path::PathExt;
-use rustc_hash::FxHashMap;
-use schema::backend_config::SyncTargets;
+use rustc_hash::{FxHashMap, FxHashSet};
+use schema::{backend_config::{BackendConfig, DEFAULT_PRESERVED_OPTION_KEYS, DEFAULT_SYNC_IGNORE_PATTERNS, LinkStrategy, SharedInstanceSettings, SyncConflictPolicy, SyncTargets, SyncedInstanceSetting}, instance::InstanceConfiguration};
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
 
-use crate::{directories::LauncherDirectories, BackendStateInstances};
+use crate::{directories::LauncherDirectories, hotbar_nbt::HotbarNbt, servers_dat::{ServerEntry, ServersDat}, BackendStateInstances};
 
-pub fn apply_to_instance(sync_targets: &SyncTargets, directories: &LauncherDirectories, dot_minecraft: Arc<Path>) {
+pub fn apply_to_instance(sync_targets: &SyncTargets, direction: SyncDirection, link_strategy: LinkStrategy, copy_fallback: bool, sources: &SyncSources, directories: &LauncherDirectories, dot_minecraft: Arc<Path>) -> SyncChangeReport {
     _ = std::fs::create_dir_all(&dot_minecraft);
+    let plan = plan_apply_to_instance(sync_targets, direction, sources, directories, &dot_minecraft);
+    execute_apply_plan(&plan, link_strategy, copy_fallback)
+}
 
-    let mut dir_iterator = walkdir::WalkDir::new(&dot_minecraft).into_iter();
+/// Works out what apply_to_instance would do without modifying anything
+pub fn plan_apply_to_instance(sync_targets: &SyncTargets, direction: SyncDirection, sources: &SyncSources, directories: &LauncherDirectories, dot_minecraft: &Path) -> ApplyPlan {
+    let mut plan = ApplyPlan::default();
+
+    let mut dir_iterator = walkdir::WalkDir::new(dot_minecraft).into_iter();
     while let Some(Ok(entry)) = dir_iterator.next() {
         if entry.file_type().is_dir() {
-            let Ok(relative) = entry.path().relative_to(&dot_minecraft) else {
+            let Ok(relative) = entry.path().relative_to(dot_minecraft) else {
                 dir_iterator.skip_current_dir();
                 continue;
             };
@@ -29,6 +43,24 @@ pub fn apply_to_instance(sync_targets: &SyncTargets, directories: &LauncherDirec
             let target_dir = safe_relative.to_path(&directories.synced_dir);
             if !target_dir.is_dir() {
                 dir_iterator.skip_current_dir();
+                // The synced folder was deleted, so this can only be a dead link if it points into the store
+                if let Some(target) = linking::link_target(entry.path()) && target.starts_with(&directories.synced_dir) {
+                    plan.operations.push(SyncOperation::RemoveLink {
+                        name: relative.as_str().into(),
+                        path: entry.path().into(),
+                        target: target.into(),
+                    });
+                }
+                continue;
+            }
+
+            if let Some(target) = linking::materialized_target(entry.path()) && target.starts_with(&directories.synced_dir) {
+                dir_iterator.skip_current_dir();
+                plan.operations.push(SyncOperation::RemoveLink {
+                    name: relative.as_str().into(),
+                    path: entry.path().into(),
+                    target: target.into(),
+                });
                 continue;
             }
 
@@ -40,7 +72,11 @@ pub fn apply_to_instance(sync_targets: &SyncTargets, directories: &LauncherDirec
 
                 if target.starts_with(&directories.synced_dir) {
                     dir_iterator.skip_current_dir();
-                    _ = junction::delete(entry.path());
+                    plan.operations.push(SyncOperation::RemoveLink {
+                        name: relative.as_str().into(),
+                        path: entry.path().into(),
+                        target: target.into(),
+                    });
                     continue;
                 }
             }
@@ -48,7 +84,7 @@ pub fn apply_to_instance(sync_targets: &SyncTargets, directories: &LauncherDirec
 
         #[cfg(unix)]
         if entry.file_type().is_symlink() {
-            let Ok(relative) = entry.path().relative_to(&dot_minecraft) else {
+            let Ok(relative) = entry.path().relative_to(dot_minecraft) else {
                 continue;
             };
             if sync_targets.folders.contains(relative.as_str()) {
@@ -59,32 +95,16 @@ pub fn apply_to_instance(sync_targets: &SyncTargets, directories: &LauncherDirec
             };
 
             if target.starts_with(&directories.synced_dir) {
-                _ = std::fs::remove_file(entry.path());
+                plan.operations.push(SyncOperation::RemoveLink {
+                    name: relative.as_str().into(),
+                    path: entry.path().into(),
+                    target: target.into(),
+                });
             }
         }
     }
 
-    for file_target in sync_targets.files.iter() {
-        if &**file_target == "options.txt" {
-            let fallback = &directories.synced_dir.join("fallback_options.txt");
-            let target = dot_minecraft.join("options.txt");
-            let combined = create_combined_options_txt(fallback, &target, directories);
-            _ = crate::write_safe(&fallback, combined.as_bytes());
-            _ = crate::write_safe(&target, combined.as_bytes());
-        } else if let Some(path) = SafePath::new(file_target) {
-            if let Some(latest) = find_latest(&path, directories) {
-                let target = path.to_path(&dot_minecraft);
-                if latest != target {
-                    if let Some(parent) = target.parent() {
-                        _ = std::fs::create_dir_all(parent);
-                    }
-                    _ = std::fs::copy(latest, target);
-                }
-            }
-        } else {
-            log::warn!("Skipping file sync target because it is not a safe path: {}", file_target);
-        }
-    }
+    plan_file_targets(&mut plan, sync_targets, direction, sources, directories, dot_minecraft);
 
     for folder_target in sync_targets.folders.iter() {
         let Some(path) = SafePath::new(folder_target) else {
@@ -92,57 +112,643 @@ pub fn apply_to_instance(sync_targets: &SyncTargets, directories: &LauncherDirec
             continue;
         };
 
+        // Nested targets are already synced as part of an enabled parent, linking them too would create a link inside the store
+        if has_parent_target(&path, sync_targets) {
+            continue;
+        }
+
         let target_dir = path.to_path(&directories.synced_dir);
-        let path = path.to_path(&dot_minecraft);
+        let path = path.to_path(dot_minecraft);
+        if covered_by_parent_link(&path, dot_minecraft, directories) {
+            continue;
+        }
+        // The folder itself is allowed to be a link, but the folders containing it aren't
+        if let Some(parent) = path.parent() && !resolves_within(dot_minecraft, parent) {
+            log::warn!("Skipping folder sync target because it resolves outside of {:?}: {}", dot_minecraft, folder_target);
+            continue;
+        }
 
         if !path.exists() {
-            _ = std::fs::create_dir_all(&target_dir);
+            match linking::link_target(&path) {
+                // A dead link, usually because the synced folder was deleted
+                Some(existing) if existing == target_dir => {
+                    plan.operations.push(SyncOperation::RepairLink {
+                        name: folder_target.clone(),
+                        path: path.into(),
+                        target: target_dir.into(),
+                    });
+                },
+                Some(existing) if existing.starts_with(&directories.synced_dir) => {
+                    plan.operations.push(SyncOperation::RemoveLink {
+                        name: folder_target.clone(),
+                        path: path.clone().into(),
+                        target: existing.into(),
+                    });
+                    plan.operations.push(SyncOperation::CreateLink {
+                        name: folder_target.clone(),
+                        path: path.into(),
+                        target: target_dir.into(),
+                    });
+                },
+                Some(_) => {
+                    plan.blocked_folders.push(folder_target.clone());
+                },
+                None => {
+                    plan.operations.push(SyncOperation::CreateLink {
+                        name: folder_target.clone(),
+                        path: path.into(),
+                        target: target_dir.into(),
+                    });
+                },
+            }
+        } else if linking::materialized_target(&path).is_some_and(|target| target == target_dir) {
+            plan.operations.push(SyncOperation::RefreshFolder {
+                name: folder_target.clone(),
+                path: path.into(),
+                target: target_dir.into(),
+            });
+        } else if !linking::is_targeting(&target_dir, &path) {
+            plan.blocked_folders.push(folder_target.clone());
+        }
+    }
+
+    plan
+}
+
+/// Only syncs the file targets, leaving folder links alone. Used when a file target changes while the game is running
+/// and when a single file target is pushed from one instance
+pub fn apply_files_to_instance(sync_targets: &SyncTargets, direction: SyncDirection, link_strategy: LinkStrategy, copy_fallback: bool, sources: &SyncSources, directories: &LauncherDirectories, dot_minecraft: &Path) -> SyncChangeReport {
+    let mut plan = ApplyPlan::default();
+    plan_file_targets(&mut plan, sync_targets, direction, sources, directories, dot_minecraft);
+    execute_apply_plan(&plan, link_strategy, copy_fallback)
+}
+
+fn plan_file_targets(plan: &mut ApplyPlan, sync_targets: &SyncTargets, direction: SyncDirection, sources: &SyncSources, directories: &LauncherDirectories, dot_minecraft: &Path) {
+    for file_target in expand_file_targets(sync_targets, sources, directories).iter() {
+        let Some(path) = SafePath::new(file_target) else {
+            log::warn!("Skipping file sync target because it is not a safe path: {}", file_target);
+            continue;
+        };
+
+        let store = store_file_path(&path, directories);
+        let target = path.to_path(dot_minecraft);
+        if !resolves_within(dot_minecraft, &target) {
+            log::warn!("Skipping file sync target because it resolves outside of {:?}: {}", dot_minecraft, file_target);
+            continue;
+        }
+
+        match direction {
+            SyncDirection::Auto if &**file_target == "options.txt" => {
+                let Some(combined) = create_combined_options_txt(&store, &target, sources, directories) else {
+                    log::warn!("Leaving {:?} as-is because it exists but can't be read", target);
+                    continue;
+                };
+                plan_write_options(plan, target, store, combined);
+            },
+            SyncDirection::Push if &**file_target == "options.txt" => {
+                if !store.is_file() {
+                    continue;
+                }
+                let Some(pushed) = create_pushed_options_txt(&store, &target, sources) else {
+                    log::warn!("Leaving {:?} as-is because it exists but can't be read", target);
+                    continue;
+                };
+                plan_write_options(plan, target, store, pushed);
+            },
+            SyncDirection::Auto if &**file_target == "servers.dat" => {
+                let Some(combined) = create_combined_servers_dat(&store, sources, directories) else {
+                    continue;
+                };
+                let contents = combined.to_bytes();
+
+                let unchanged = |path: &Path| std::fs::read(path).is_ok_and(|existing| existing == contents);
+                if !unchanged(&target) || !unchanged(&store) {
+                    plan.operations.push(SyncOperation::WriteServers {
+                        path: target.into(),
+                        store: store.into(),
+                        contents: contents.into(),
+                    });
+                }
+            },
+            SyncDirection::Auto if &**file_target == "hotbar.nbt" => {
+                let Some(combined) = create_combined_hotbar_nbt(&store, sources, directories) else {
+                    continue;
+                };
+                let contents = combined.to_bytes();
+
+                let unchanged = |path: &Path| std::fs::read(path).is_ok_and(|existing| existing == contents);
+                if !unchanged(&target) || !unchanged(&store) {
+                    plan.operations.push(SyncOperation::WriteHotbars {
+                        path: target.into(),
+                        store: store.into(),
+                        contents: contents.into(),
+                    });
+                }
+            },
+            SyncDirection::Auto => {
+                if let Some(latest) = find_latest(&path, sources, directories) {
+                    if files_differ(&latest, &target) {
+                        plan.operations.push(SyncOperation::CopyFile {
+                            name: file_target.clone(),
+                            from: latest.as_path().into(),
+                            to: target.into(),
+                            to_store: false,
+                        });
+                    }
+                    if files_differ(&latest, &store) {
+                        plan.operations.push(SyncOperation::CopyFile {
+                            name: file_target.clone(),
+                            from: latest.into(),
+                            to: store.into(),
+                            to_store: true,
+                        });
+                    }
+                }
+            },
+            SyncDirection::Push => {
+                if store.is_file() && files_differ(&store, &target) {
+                    plan.operations.push(SyncOperation::CopyFile {
+                        name: file_target.clone(),
+                        from: store.into(),
+                        to: target.into(),
+                        to_store: false,
+                    });
+                }
+            },
+            SyncDirection::Pull => {
+                // Pulling only updates the store, which is done once by pull_into_store
+            },
+        }
+    }
+}
+
+fn plan_write_options(plan: &mut ApplyPlan, target: PathBuf, store: PathBuf, contents: String) {
+    let before = read_options_txt(&target);
+    let mut changed_keys = Vec::new();
+    for line in contents.lines() {
+        if let Some((key, value)) = line.split_once(':') && before.get(key).map(String::as_str) != Some(value) {
+            changed_keys.push(key.into());
+        }
+    }
+    changed_keys.sort();
+
+    let unchanged = |path: &Path| std::fs::read(path).is_ok_and(|existing| existing == contents.as_bytes());
+    if !unchanged(&target) || !unchanged(&store) {
+        plan.operations.push(SyncOperation::WriteOptions {
+            path: target.into(),
+            store: store.into(),
+            contents: contents.into(),
+            changed_keys,
+        });
+    }
+}
+
+/// Whether `name`, relative to .minecraft or the synced folder, is one of the file targets or matches one of the patterns
+pub fn is_file_target(name: &str, sync_targets: &SyncTargets) -> bool {
+    let name = if name == "fallback_options.txt" { "options.txt" } else { name };
+    let components: Vec<&str> = name.split('/').collect();
+    sync_targets.files.iter().any(|file_target| {
+        if is_path_pattern(file_target) {
+            let pattern: Vec<&str> = file_target.split('/').filter(|component| !component.is_empty()).collect();
+            path_pattern_matches(&pattern, &components)
+        } else {
+            &**file_target == name
+        }
+    })
+}
+
+/// Whether `path` is a link that points into the synced folder, i.e. one created by syncing
+pub fn is_link_into_store(path: &Path, directories: &LauncherDirectories) -> bool {
+    linking::link_target(path).is_some_and(|target| target.starts_with(&directories.synced_dir))
+}
+
+fn has_parent_target(path: &SafePath, sync_targets: &SyncTargets) -> bool {
+    sync_targets.folders.iter().any(|folder| {
+        &**folder != path.as_str() && SafePath::new(folder).is_some_and(|folder| path.starts_with(folder.as_str()))
+    })
+}
+
+/// Whether one of the folders containing `path` inside `dot_minecraft` is linked into the store,
+/// meaning `path` is already synced through that link
+fn covered_by_parent_link(path: &Path, dot_minecraft: &Path, directories: &LauncherDirectories) -> bool {
+    let mut current = path.parent();
+    while let Some(parent) = current && parent != dot_minecraft && parent.starts_with(dot_minecraft) {
+        if linking::link_target(parent).is_some_and(|target| target.starts_with(&directories.synced_dir)) {
+            return true;
+        }
+        current = parent.parent();
+    }
+    false
+}
+
+/// Whether `path` stays inside `base` once symlinks and junctions are followed. SafePath rejects `..`, but a
+/// user-created link in one of the components could still point somewhere else, e.g. into a shared folder
+fn resolves_within(base: &Path, path: &Path) -> bool {
+    let Ok(base) = base.canonicalize() else {
+        return false;
+    };
+    let mut current = Some(path);
+    while let Some(existing) = current {
+        if let Ok(resolved) = existing.canonicalize() {
+            return resolved.starts_with(&base);
+        }
+        current = existing.parent();
+    }
+    false
+}
+
+pub fn execute_apply_plan(plan: &ApplyPlan, link_strategy: LinkStrategy, copy_fallback: bool) -> SyncChangeReport {
+    // Links are removed first because a dead link can be replaced with a new one at the same path. The operations
+    // within each batch touch different paths, and create_dir_all copes with parents being created concurrently
+    let (removals, others): (Vec<&SyncOperation>, Vec<&SyncOperation>) = plan.operations.iter()
+        .partition(|operation| matches!(operation, SyncOperation::RemoveLink { .. }));
+    let (options, others): (Vec<&SyncOperation>, Vec<&SyncOperation>) = others.into_iter()
+        .partition(|operation| matches!(operation, SyncOperation::WriteOptions { .. }));
+
+    // The operations run on other threads, which need to know whether this one is sending progress
+    let silenced = PROGRESS_SILENCED.get();
+
+    let mut report = SyncChangeReport::default();
+    for batch in [removals, others] {
+        let reports: Vec<SyncChangeReport> = batch.par_iter()
+            .map(|operation| with_progress_silenced(silenced, || execute_operation(operation, link_strategy, copy_fallback)))
+            .collect();
+        for operation_report in reports {
+            report.append(operation_report);
+        }
+    }
+    report.append(write_options(&options, write_keeping_permissions));
+
+    for error in &report.errors {
+        log::warn!("{}", error.message);
+    }
+
+    report
+}
+
+fn execute_operation(operation: &SyncOperation, link_strategy: LinkStrategy, copy_fallback: bool) -> SyncChangeReport {
+    let mut report = SyncChangeReport::default();
+
+    match operation {
+        SyncOperation::RemoveLink { name, path, target } => {
+            match linking::unlink_dir_if_targeting(target, path) {
+                Ok(()) => {
+                    log::info!("Removed link {:?} to {:?}", path, target);
+                    report.links_removed.push(name.clone());
+                },
+                Err(error) => report.errors.push(sync_error(name, format!("Unable to unlink {}: {}", name, error))),
+            }
+        },
+        SyncOperation::CreateLink { name, path, target } => {
+            _ = std::fs::create_dir_all(target);
             if let Some(parent) = path.parent() {
                 _ = std::fs::create_dir_all(parent);
             }
-            _ = linking::link_dir(&target_dir, &path);
+            if matches!(link_strategy, LinkStrategy::Hardlink | LinkStrategy::Copy) {
+                start_progress(name, count_synced_files(target));
+            }
+            match linking::link_dir(target, path, link_strategy, copy_fallback) {
+                Ok(()) => report.links_created.push(name.clone()),
+                Err(error @ linking::LinkError::NeedsPrivilege(_)) => {
+                    log::warn!("Not allowed to link {:?} to {:?}, enable copying folders that can't be linked to sync it anyway", path, target);
+                    report.errors.push(sync_error(name, format!("Unable to link {}: {}", name, error)));
+                },
+                Err(error) => report.errors.push(sync_error(name, format!("Unable to link {}: {}", name, error))),
+            }
+            finish_progress();
+        },
+        SyncOperation::RepairLink { name, path, target } => {
+            match std::fs::create_dir_all(target) {
+                Ok(()) => {
+                    log::info!("Recreated missing synced folder {:?} linked from {:?}", target, path);
+                    report.links_repaired.push(name.clone());
+                },
+                Err(error) => report.errors.push(sync_error(name, format!("Unable to recreate the synced {} folder: {}", name, error))),
+            }
+        },
+        SyncOperation::RefreshFolder { name, path, target } => {
+            // Both sides are walked, once in each direction
+            start_progress(name, count_synced_files(target) + count_synced_files(path));
+            if let Err(error) = linking::refresh_if_materialized(target, path) {
+                report.errors.push(sync_error(name, format!("Unable to refresh {}: {}", name, error)));
+            }
+            finish_progress();
+        },
+        SyncOperation::CopyFile { name, from, to, to_store } => {
+            match copy_file_if_different(from, to) {
+                Ok(copied) => {
+                    if copied && !to_store {
+                        report.files_copied.push(name.clone());
+                    }
+                },
+                Err(error) => report.errors.push(sync_error(name, format!("Unable to copy {}: {}", name, error))),
+            }
+        },
+        SyncOperation::WriteServers { path, store, contents } => {
+            write_merged_file("servers.dat", path, store, contents, &mut report);
+        },
+        SyncOperation::WriteHotbars { path, store, contents } => {
+            write_merged_file("hotbar.nbt", path, store, contents, &mut report);
+        },
+        SyncOperation::WriteOptions { .. } => {
+            return write_options(&[operation], write_keeping_permissions);
+        },
+    }
+
+    report
+}
+
+/// Writes the combined options.txt into every instance before the synced copy. Each write is atomic on its own, and
+/// if one of the instance writes fails the ones already written are restored and the synced copy is left alone, so
+/// the next run merges again from the same files
+fn write_options(operations: &[&SyncOperation], write: impl Fn(&Path, &[u8]) -> std::io::Result<()>) -> SyncChangeReport {
+    let mut report = SyncChangeReport::default();
+    let mut written: Vec<(&Path, Option<Vec<u8>>)> = Vec::new();
+    let mut changed = Vec::new();
+    let mut stores: Vec<(&Path, &str)> = Vec::new();
+
+    for operation in operations {
+        let SyncOperation::WriteOptions { path, store, contents, changed_keys } = operation else {
+            continue;
+        };
+        let path: &Path = path;
+        let previous = std::fs::read(path).ok();
+
+        if let Err(error) = write(path, contents.as_bytes()) {
+            report.errors.push(sync_error("options.txt", format!("Unable to write options.txt: {}", error)));
+            for (path, previous) in written.into_iter().rev() {
+                let restored = match previous {
+                    Some(previous) => write(path, &previous),
+                    None => std::fs::remove_file(path),
+                };
+                if let Err(error) = restored {
+                    report.errors.push(sync_error("options.txt", format!("Unable to restore {:?}: {}", path, error)));
+                }
+            }
+            return report;
+        }
+
+        written.push((path, previous));
+        changed.extend(changed_keys.iter().cloned());
+        stores.push((&**store, &**contents));
+    }
+
+    report.options_changed = changed;
+    for (store, contents) in stores {
+        if let Err(error) = write(store, contents.as_bytes()) {
+            report.errors.push(sync_error("options.txt", format!("Unable to write the synced options.txt: {}", error)));
+        }
+    }
+    report
+}
+
+/// Writes a file that was merged from every instance to both the synced folder and the instance
+fn write_merged_file(name: &str, path: &Path, store: &Path, contents: &[u8], report: &mut SyncChangeReport) {
+    if let Err(error) = write_keeping_permissions(store, contents) {
+        report.errors.push(sync_error(name, format!("Unable to write the synced {}: {}", name, error)));
+    }
+    if !std::fs::read(path).is_ok_and(|existing| *existing == *contents) {
+        match write_keeping_permissions(path, contents) {
+            Ok(()) => report.files_copied.push(name.into()),
+            Err(error) => report.errors.push(sync_error(name, format!("Unable to write {}: {}", name, error))),
         }
     }
 }
 
-fn find_latest(filename: &SafePath, directories: &LauncherDirectories) -> Option<PathBuf> {
-    let mut latest_time = SystemTime::UNIX_EPOCH;
-    let mut latest_path = None;
+/// write_safe replaces the file with a new one, which would otherwise drop permissions the user set on the old one
+fn write_keeping_permissions(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let permissions = std::fs::metadata(path).ok().map(|metadata| metadata.permissions());
+    crate::write_safe(path, contents)?;
+    if let Some(permissions) = permissions {
+        std::fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+/// std::fs::copy already keeps the permissions, the modification time is kept as well so that tools
+/// relying on timestamps don't see every synced copy as freshly changed. That part is best-effort,
+/// a read-only copy can't be opened for writing to set it
+fn copy_keeping_metadata(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::fs::copy(source, target)?;
+    if let Ok(modified) = std::fs::metadata(source).and_then(|metadata| metadata.modified())
+        && let Ok(file) = std::fs::File::options().write(true).open(target)
+    {
+        _ = file.set_modified(modified);
+    }
+    Ok(())
+}
 
-    let read_dir = std::fs::read_dir(&directories.instances_dir).ok()?;
+fn sync_error(target: &str, message: String) -> SyncError {
+    SyncError {
+        target: target.into(),
+        message: message.into(),
+    }
+}
 
-    for entry in read_dir {
-        let Ok(entry) = entry else {
+/// Copies the newest copy of each file target from the instances into the store
+pub fn pull_into_store(sync_targets: &SyncTargets, sources: &SyncSources, directories: &LauncherDirectories) {
+    for file_target in expand_file_targets(sync_targets, sources, directories).iter() {
+        let Some(path) = SafePath::new(file_target) else {
             continue;
         };
+        if let Some(latest) = find_latest(&path, sources, directories)
+            && let Err(error) = copy_file_if_different(&latest, &store_file_path(&path, directories))
+        {
+            log::warn!("Unable to pull {} into the synced folder: {}", file_target, error);
+        }
+    }
+}
 
-        let path = filename.to_path(&entry.path().join(".minecraft"));
+/// Patterns stop expanding after this many files, so that something like `**` can't make every sync walk a huge tree
+const MAX_PATTERN_MATCHES: usize = 256;
 
-        if let Ok(metadata) = std::fs::metadata(&path) {
-            let mut time = SystemTime::UNIX_EPOCH;
+/// The file targets with every pattern replaced by the files it matches in the instances that sync
+fn expand_file_targets(sync_targets: &SyncTargets, sources: &SyncSources, directories: &LauncherDirectories) -> Vec<Arc<str>> {
+    if !sync_targets.files.iter().any(|file_target| is_path_pattern(file_target)) {
+        return sync_targets.files.iter().cloned().collect();
+    }
 
-            if let Ok(created) = metadata.created() {
-                time = time.max(created);
+    let mut roots = Vec::new();
+    if let Ok(read_dir) = std::fs::read_dir(&directories.instances_dir) {
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            if !sources.sync_disabled.contains(&path) {
+                roots.push(path.join(".minecraft"));
             }
-            if let Ok(modified) = metadata.modified() {
-                time = time.max(modified);
+        }
+    }
+
+    let mut expanded = BTreeSet::new();
+    for file_target in sync_targets.files.iter() {
+        if is_path_pattern(file_target) {
+            expanded.extend(expand_file_pattern(file_target, &roots));
+        } else {
+            expanded.insert(file_target.clone());
+        }
+    }
+    expanded.into_iter().collect()
+}
+
+/// Finds the files matching `pattern` relative to any of the roots. `*` and `?` match within a single path
+/// component and a `**` component matches any number of folders
+fn expand_file_pattern(pattern: &str, roots: &[PathBuf]) -> BTreeSet<Arc<str>> {
+    let mut matches = BTreeSet::new();
+    if !is_safe_path_pattern(pattern) {
+        log::warn!("Skipping file sync pattern because it can match unsafe paths: {}", pattern);
+        return matches;
+    }
+
+    let components: Vec<&str> = pattern.split('/').filter(|component| !component.is_empty()).collect();
+    // Only the folders below the part of the pattern without wildcards need to be walked
+    let literal_count = components.iter().take_while(|component| !is_path_pattern(component)).count();
+    let prefix = components[..literal_count].join("/");
+    let max_depth = if components.contains(&"**") {
+        usize::MAX
+    } else {
+        components.len() - literal_count
+    };
+
+    for root in roots {
+        let base = match SafePath::new(&prefix) {
+            Some(prefix) => prefix.to_path(root),
+            None => root.clone(),
+        };
+        for entry in walkdir::WalkDir::new(&base).min_depth(1).max_depth(max_depth).into_iter().flatten() {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(relative) = entry.path().relative_to(root) else {
+                continue;
+            };
+            let path_components: Vec<&str> = relative.components().map(|component| component.as_str()).collect();
+            if !path_pattern_matches(&components, &path_components) || SafePath::from_relative_path(&relative).is_none() {
+                continue;
             }
 
-            if latest_path.is_none() || time > latest_time {
-                latest_time = time;
-                latest_path = Some(path);
+            matches.insert(relative.as_str().into());
+            if matches.len() >= MAX_PATTERN_MATCHES {
+                log::warn!("File sync pattern {} matches more than {} files, the rest are not synced", pattern, MAX_PATTERN_MATCHES);
+                return matches;
             }
         }
     }
 
-    latest_path
+    matches
 }
 
-fn create_combined_options_txt(fallback: &Path, current: &Path, directories: &LauncherDirectories) -> String {
-    let mut values = read_options_txt(fallback);
+fn path_pattern_matches(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skipped| path_pattern_matches(rest, &path[skipped..])),
+        Some((component, rest)) => {
+            !path.is_empty() && glob_matches(component, path[0]) && path_pattern_matches(rest, &path[1..])
+        },
+    }
+}
+
+/// Where the store keeps its copy of a file target. Folder targets are linked rather than copied so they don't have one
+fn store_file_path(path: &SafePath, directories: &LauncherDirectories) -> PathBuf {
+    if path.as_str() == "options.txt" {
+        directories.synced_dir.join("fallback_options.txt")
+    } else {
+        path.to_path(&directories.synced_dir)
+    }
+}
+
+/// Returns true if the file was copied, which is skipped if the target already has the same contents
+fn copy_file_if_different(source: &Path, target: &Path) -> std::io::Result<bool> {
+    if !files_differ(source, target) {
+        return Ok(false);
+    }
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    copy_keeping_metadata(source, target)?;
+    Ok(true)
+}
+
+fn files_differ(source: &Path, target: &Path) -> bool {
+    if source == target {
+        return false;
+    }
+    let Ok(existing) = std::fs::read(target) else {
+        return true;
+    };
+    !std::fs::read(source).is_ok_and(|contents| contents == existing)
+}
+
+/// Which instance copies of a file can be used as the source when syncing it, and how to choose between them
+pub struct SyncSources {
+    /// Root folders of the instances that have file syncing disabled, these are never used as a source
+    pub sync_disabled: FxHashSet<PathBuf>,
+    pub conflict_policy: SyncConflictPolicy,
+    /// Overrides DEFAULT_PRESERVED_OPTION_KEYS when not empty
+    pub preserved_option_keys: Vec<Arc<str>>,
+}
+
+impl SyncSources {
+    pub fn new(instances: &mut BackendStateInstances, conflict_policy: SyncConflictPolicy) -> Self {
+        let sync_disabled = instances.instances.iter_mut()
+            .filter_map(|instance| instance.configuration.get().disable_file_syncing.then(|| instance.root_path.to_path_buf()))
+            .collect();
+
+        Self {
+            sync_disabled,
+            conflict_policy,
+            preserved_option_keys: Vec::new(),
+        }
+    }
+
+    fn is_preserved_option(&self, key: &str) -> bool {
+        if self.preserved_option_keys.is_empty() {
+            DEFAULT_PRESERVED_OPTION_KEYS.contains(&key)
+        } else {
+            self.preserved_option_keys.iter().any(|pattern| glob_matches(pattern, key))
+        }
+    }
+}
+
+/// Chooses which instance's copy of a file to sync from, according to the conflict policy
+fn find_latest(filename: &SafePath, sources: &SyncSources, directories: &LauncherDirectories) -> Option<PathBuf> {
+    let copies: Vec<PathBuf> = instance_copies_oldest_first(Path::new(filename.as_str()), sources, directories)
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect();
+
+    match sources.conflict_policy {
+        SyncConflictPolicy::LatestMtime => copies.into_iter().last(),
+        SyncConflictPolicy::LargestFile => {
+            // Later copies are newer, so enumerating breaks ties by modification time
+            copies.into_iter()
+                .enumerate()
+                .max_by_key(|(index, path)| (std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0), *index))
+                .map(|(_, path)| path)
+        },
+        SyncConflictPolicy::MajorityHash => {
+            // Hash -> (number of copies, index of the newest copy, newest copy)
+            let mut versions: FxHashMap<String, (usize, usize, PathBuf)> = FxHashMap::default();
+            for (index, path) in copies.into_iter().enumerate() {
+                let Ok(contents) = std::fs::read(&path) else {
+                    continue;
+                };
+                let version = versions.entry(hex::encode(Sha1::digest(&contents))).or_insert((0, index, path.clone()));
+                version.0 += 1;
+                version.1 = index;
+                version.2 = path;
+            }
+
+            versions.into_values()
+                .max_by_key(|(count, newest, _)| (*count, *newest))
+                .map(|(_, _, path)| path)
+        },
+    }
+}
 
+/// Every instance's copy of a file, whether or not it exists, from oldest to newest. Copies with
+/// identical timestamps are sorted by path so that they're always merged in the same order
+fn instance_copies_oldest_first(relative: &Path, sources: &SyncSources, directories: &LauncherDirectories) -> Vec<PathBuf> {
     let Ok(read_dir) = std::fs::read_dir(&directories.instances_dir) else {
-        return create_options_txt(values);
+        return Vec::new();
     };
 
     let mut paths = Vec::new();
@@ -153,8 +759,11 @@ fn create_combined_options_txt(fallback: &Path, current: &Path, directories: &La
         };
 
         let mut path = entry.path();
+        if sources.sync_disabled.contains(&path) {
+            continue;
+        }
         path.push(".minecraft");
-        path.push("options.txt");
+        path.push(relative);
 
         let mut time = SystemTime::UNIX_EPOCH;
 
@@ -170,14 +779,112 @@ fn create_combined_options_txt(fallback: &Path, current: &Path, directories: &La
         paths.push((time, path));
     }
 
-    paths.sort_by_key(|(time, _)| *time);
+    paths.sort();
+    paths.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Unions the server lists of every instance, deduplicating by address. A newer copy of a server replaces the name
+/// and icon of an older one, while tags only the older copy has are kept. Unreadable files are skipped. Since
+/// this is a union, a server removed from one instance comes back if another instance still has it
+fn create_combined_servers_dat(fallback: &Path, sources: &SyncSources, directories: &LauncherDirectories) -> Option<ServersDat> {
+    let mut combined: Option<ServersDat> = None;
+    let mut servers: Vec<ServerEntry> = Vec::new();
+    let mut index_by_key: FxHashMap<String, usize> = FxHashMap::default();
+
+    let mut paths = vec![fallback.to_path_buf()];
+    paths.extend(instance_copies_oldest_first(Path::new("servers.dat"), sources, directories));
+
+    for path in paths {
+        if !path.is_file() {
+            continue;
+        }
+        let servers_dat = match ServersDat::read(&path) {
+            Ok(servers_dat) => servers_dat,
+            Err(error) => {
+                log::warn!("Skipping unreadable servers.dat {:?} while merging: {error:?}", path);
+                continue;
+            },
+        };
+
+        for mut server in servers_dat.servers() {
+            let Some(key) = server.key() else {
+                continue;
+            };
+            if let Some(index) = index_by_key.get(&key) {
+                server.fill_missing_from(&servers[*index]);
+                servers[*index] = server;
+            } else {
+                index_by_key.insert(key, servers.len());
+                servers.push(server);
+            }
+        }
+
+        // The newest readable file provides any other root tags
+        combined = Some(servers_dat);
+    }
+
+    let mut combined = combined?;
+    combined.set_servers(&servers);
+    Some(combined)
+}
+
+/// Takes each saved hotbar from the most recently modified copy where it holds items, so that saving a hotbar in
+/// one instance doesn't wipe the ones saved in another. Unreadable files are skipped and the newest readable file
+/// provides the other tags. Like servers.dat, a hotbar cleared in one instance comes back if another still has it
+fn create_combined_hotbar_nbt(fallback: &Path, sources: &SyncSources, directories: &LauncherDirectories) -> Option<HotbarNbt> {
+    let mut paths = vec![fallback.to_path_buf()];
+    paths.extend(instance_copies_oldest_first(Path::new("hotbar.nbt"), sources, directories));
+
+    let mut copies = Vec::new();
+    for path in paths {
+        if !path.is_file() {
+            continue;
+        }
+        match HotbarNbt::read(&path) {
+            Ok(hotbars) => copies.push(hotbars),
+            Err(error) => log::warn!("Skipping unreadable hotbar.nbt {:?} while merging: {error:?}", path),
+        }
+    }
+
+    let mut combined = copies.pop()?;
+    for row in 0..HotbarNbt::ROWS {
+        if combined.has_items(row) {
+            continue;
+        }
+        if let Some(source) = copies.iter().rev().find(|copy| copy.has_items(row)) {
+            combined.copy_row_from(row, source);
+        }
+    }
+    Some(combined)
+}
+
+/// Returns None if `current` exists but can't be read, since writing the combined file
+/// would lose the keys that are kept per-instance
+fn create_combined_options_txt(fallback: &Path, current: &Path, sources: &SyncSources, directories: &LauncherDirectories) -> Option<String> {
+    if try_read_options_txt(current).is_err() {
+        return None;
+    }
+
+    let mut values = read_options_txt(fallback);
+
+    let mut latest_order = Vec::new();
 
-    for (_, path) in paths {
-        let mut new_values = read_options_txt(&path);
+    for path in instance_copies_oldest_first(Path::new("options.txt"), sources, directories) {
+        let mut new_values = match try_read_options_txt(&path) {
+            Ok(Some(values)) => values,
+            Ok(None) => continue,
+            Err(error) => {
+                log::warn!("Ignoring {:?} while combining options.txt: {}", path, error);
+                continue;
+            },
+        };
 
         if path != current {
-            new_values.remove("resourcePacks");
-            new_values.remove("incompatibleResourcePacks");
+            new_values.retain(|key, _| !sources.is_preserved_option(key));
+        }
+
+        if !new_values.is_empty() {
+            latest_order = new_values.keys().cloned().collect();
         }
 
         for (key, value) in new_values {
@@ -185,13 +892,38 @@ fn create_combined_options_txt(fallback: &Path, current: &Path, directories: &La
         }
     }
 
-    create_options_txt(values)
+    // Keep the key order of the most recently modified file so that the output doesn't churn between syncs
+    let mut ordered = IndexMap::with_capacity(values.len());
+    for key in latest_order {
+        if let Some(value) = values.shift_remove(&key) {
+            ordered.insert(key, value);
+        }
+    }
+    ordered.extend(values);
+
+    Some(create_options_txt(ordered))
 }
 
-fn create_options_txt(values: FxHashMap<String, String>) -> String {
+/// The synced options.txt with the keys that are kept per-instance taken from `current`, so pushing doesn't
+/// reset them the way copying the file would. Returns None if `current` exists but can't be read
+fn create_pushed_options_txt(store: &Path, current: &Path, sources: &SyncSources) -> Option<String> {
+    let current = try_read_options_txt(current).ok()?.unwrap_or_default();
+    let mut values = read_options_txt(store);
+    for (key, value) in current {
+        if sources.is_preserved_option(&key) {
+            values.insert(key, value);
+        }
+    }
+    Some(create_options_txt(values))
+}
+
+fn create_options_txt(values: IndexMap<String, String>) -> String {
     let mut options = String::new();
 
     for (key, value) in values {
+        if key.is_empty() {
+            continue;
+        }
         options.push_str(&key);
         options.push(':');
         options.push_str(&value);
@@ -201,56 +933,145 @@ fn create_options_txt(values: FxHashMap<String, String>) -> String {
     options
 }
 
-fn read_options_txt(path: &Path) -> FxHashMap<String, String> {
-    let Ok(content) = std::fs::read_to_string(path) else {
-        return FxHashMap::default();
+fn read_options_txt(path: &Path) -> IndexMap<String, String> {
+    try_read_options_txt(path).ok().flatten().unwrap_or_default()
+}
+
+/// Ok(None) if the file doesn't exist, invalid UTF-8 is replaced rather than treated as unreadable
+fn try_read_options_txt(path: &Path) -> std::io::Result<Option<IndexMap<String, String>>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
     };
+    let content = String::from_utf8_lossy(&bytes);
 
-    let mut values = FxHashMap::default();
-    for line in content.split('\n') {
+    let mut values = IndexMap::default();
+    // lines() handles both LF and CRLF, only the first colon separates the key so values can contain colons.
+    // Some modded files contain blank lines or `#` annotations, those aren't options even if they contain a colon
+    for line in content.lines() {
         let line = line.trim_ascii();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
         if let Some((key, value)) = line.split_once(':') {
-            values.insert(key.to_string(), value.to_string());
+            let key = key.trim_ascii();
+            if !key.is_empty() {
+                values.insert(key.to_string(), value.to_string());
+            }
         }
     }
-    values
+    Ok(Some(values))
 }
 
-pub fn get_sync_state(sync_targets: &SyncTargets, instances: &mut BackendStateInstances, directories: &LauncherDirectories) -> std::io::Result<SyncState> {
-    let mut dot_minecraft_paths = Vec::new();
+pub fn get_sync_state(config: &BackendConfig, include_details: bool, instances: &mut BackendStateInstances, directories: &LauncherDirectories) -> std::io::Result<SyncState> {
+    let mut candidates = Vec::new();
 
     for instance in instances.instances.iter_mut() {
-        if !instance.configuration.get().disable_file_syncing {
-            dot_minecraft_paths.push(instance.dot_minecraft_path.clone());
+        let configuration = instance.configuration.get();
+        if !configuration.disable_file_syncing {
+            candidates.push((instance.dot_minecraft_path.clone(), instance.name, configuration.clone()));
         }
     }
 
-    let total = dot_minecraft_paths.len();
-    let mut entries = BTreeMap::default();
+    let matches_filter = |target: &str, name: &str, configuration: &InstanceConfiguration| {
+        config.sync_target_filters.get(target).is_none_or(|filter| instance_matches_filter(filter, name, configuration))
+    };
 
-    for file_target in sync_targets.files.iter() {
-        if let Some(safe_file_target) = SafePath::new(file_target) {
-            let mut cannot_sync_count = 0;
+    // Instances that opted out of a target are counted separately, they aren't expected to sync it
+    let matching_paths = |target: &str| -> Vec<Arc<Path>> {
+        candidates.iter()
+            .filter(|(_, name, configuration)| matches_filter(target, name, configuration) && !configuration.sync_exclusions.contains(target))
+            .map(|(path, _, _)| path.clone())
+            .collect()
+    };
 
-            for dot_minecraft in &dot_minecraft_paths {
-                let target = safe_file_target.to_path(dot_minecraft);
-                if target.is_dir() {
-                    cannot_sync_count += 1;
-                }
-            }
+    let count_excluded = |target: &str| -> usize {
+        candidates.iter()
+            .filter(|(_, name, configuration)| matches_filter(target, name, configuration) && configuration.sync_exclusions.contains(target))
+            .count()
+    };
+
+    let instance_name = |dot_minecraft: &Arc<Path>| -> Option<Arc<str>> {
+        candidates.iter()
+            .find(|(path, _, _)| path == dot_minecraft)
+            .map(|(_, name, _)| name.as_str().into())
+    };
+
+    let sync_targets = &config.sync_targets;
+    let total = candidates.len();
+    let mut entries = BTreeMap::default();
+
+    let mut by_identity: BTreeMap<PathBuf, Vec<Arc<str>>> = BTreeMap::new();
+    for folder_target in sync_targets.folders.iter() {
+        if let Some(safe_path) = SafePath::new(folder_target) {
+            by_identity.entry(store_identity(&safe_path, directories)).or_default().push(folder_target.clone());
+        }
+    }
+    let mut aliases: FxHashMap<Arc<str>, Vec<Arc<str>>> = FxHashMap::default();
+    for targets in by_identity.into_values() {
+        for target in &targets {
+            let others: Vec<Arc<str>> = targets.iter().filter(|other| *other != target).cloned().collect();
+            if !others.is_empty() {
+                aliases.insert(target.clone(), others);
+            }
+        }
+    }
+
+    for file_target in sync_targets.files.iter() {
+        let dot_minecraft_paths = matching_paths(file_target);
+        let matching_count = dot_minecraft_paths.len();
+        let excluded_count = count_excluded(file_target);
+        let filter = config.sync_target_filters.get(file_target).cloned();
+
+        if is_path_pattern(file_target) && is_safe_path_pattern(file_target) {
+            let dot_minecraft_paths: Vec<PathBuf> = dot_minecraft_paths.iter().map(|path| path.to_path_buf()).collect();
+            let pattern_matches = expand_file_pattern(file_target, &dot_minecraft_paths).len();
+
+            entries.insert(file_target.clone(), SyncTargetState {
+                enabled: true,
+                is_file: true,
+                sync_count: matching_count,
+                matching_count,
+                excluded_count,
+                filter,
+                pattern_matches: Some(pattern_matches),
+                ..Default::default()
+            });
+        } else if let Some(safe_file_target) = SafePath::new(file_target) {
+            let mut cannot_sync_count = 0;
+            let mut cannot_sync_instances = Vec::new();
+
+            for dot_minecraft in &dot_minecraft_paths {
+                let target = safe_file_target.to_path(dot_minecraft);
+                if target.is_dir() {
+                    cannot_sync_count += 1;
+                    if include_details {
+                        cannot_sync_instances.extend(instance_name(dot_minecraft));
+                    }
+                }
+            }
 
             entries.insert(file_target.clone(), SyncTargetState {
                 enabled: true,
                 is_file: true,
-                sync_count: total.saturating_sub(cannot_sync_count),
+                sync_count: matching_count.saturating_sub(cannot_sync_count),
                 cannot_sync_count,
+                matching_count,
+                excluded_count,
+                filter,
+                cannot_sync_instances,
+                ..Default::default()
             });
         } else {
             entries.insert(file_target.clone(), SyncTargetState {
                 enabled: true,
                 is_file: true,
-                sync_count: 0,
-                cannot_sync_count: total,
+                cannot_sync_count: matching_count,
+                matching_count,
+                excluded_count,
+                filter,
+                ..Default::default()
             });
         }
     }
@@ -266,12 +1087,20 @@ pub fn get_sync_state(sync_targets: &SyncTargets, instances: &mut BackendStateIn
     let disabled_iter = disabled.iter().map(|f| (f, false));
 
     for (folder_target, enabled) in enabled_iter.chain(disabled_iter) {
+        let dot_minecraft_paths = matching_paths(folder_target);
+        let matching_count = dot_minecraft_paths.len();
+        let excluded_count = count_excluded(folder_target);
+        let filter = config.sync_target_filters.get(folder_target).cloned();
+
         let Some(safe_path) = SafePath::new(folder_target) else {
             entries.insert(folder_target.clone(), SyncTargetState {
                 enabled,
                 is_file: false,
-                sync_count: 0,
-                cannot_sync_count: total,
+                cannot_sync_count: matching_count,
+                matching_count,
+                excluded_count,
+                filter,
+                ..Default::default()
             });
             continue;
         };
@@ -280,32 +1109,455 @@ pub fn get_sync_state(sync_targets: &SyncTargets, instances: &mut BackendStateIn
 
         let mut sync_count = 0;
         let mut cannot_sync_count = 0;
+        let mut cannot_sync_instances = Vec::new();
+        let mut drifted = false;
 
         for dot_minecraft in &dot_minecraft_paths {
             let path = safe_path.to_path(dot_minecraft);
 
-            if linking::is_targeting(&target_dir, &path) {
+            if linking::is_targeting(&target_dir, &path) || covered_by_parent_link(&path, dot_minecraft, directories) {
                 sync_count += 1;
+                // Walks both folders, so it's left for the detailed state
+                if include_details && !drifted && linking::materialized_target(&path).is_some() {
+                    drifted = linking::has_drifted(&target_dir, &path);
+                }
             } else if path.exists() {
                 cannot_sync_count += 1;
+                if include_details {
+                    cannot_sync_instances.extend(instance_name(dot_minecraft));
+                }
             }
         }
 
+        let verified = enabled && config.verified_sync_targets.contains(folder_target);
+        let modified_files = if verified {
+            find_modified_files(&safe_path, &target_dir, directories)
+        } else {
+            Vec::new()
+        };
+
         entries.insert(folder_target.clone(), SyncTargetState {
             enabled,
             is_file: false,
             sync_count,
             cannot_sync_count,
+            matching_count,
+            excluded_count,
+            filter,
+            verified,
+            modified_files,
+            aliases: aliases.remove(folder_target).unwrap_or_default(),
+            cannot_sync_instances,
+            drifted,
+            ..Default::default()
         });
     }
 
+    if is_case_insensitive(directories) {
+        for (name, state) in entries.iter_mut() {
+            state.case_collision = find_case_variant(name, sync_targets);
+        }
+    }
+
+    if include_details {
+        for (name, state) in entries.iter_mut() {
+            if !state.enabled {
+                continue;
+            }
+            let Some(safe_path) = SafePath::new(name) else {
+                continue;
+            };
+            let path = if state.is_file {
+                store_file_path(&safe_path, directories)
+            } else {
+                safe_path.to_path(&directories.synced_dir)
+            };
+            (state.total_bytes, state.last_modified) = measure_synced_copy(&path);
+        }
+    }
+
     Ok(SyncState {
         sync_folder: directories.synced_dir.clone(),
         targets: entries,
         total_count: total,
+        synced_instance_settings: config.synced_instance_settings,
+        interrupted_operation: SyncCheckpoint::load(directories).map(|checkpoint| checkpoint.summary()),
+        paused: config.syncing_paused,
+        watch_sync: config.watch_sync,
+        link_copy_fallback: config.link_copy_fallback,
+        location_error: check_synced_dir_location(directories, instances).err().map(|error| error.to_string().into()),
+    })
+}
+
+/// Checks an instance against a sync target filter. A filter is a list of space-separated terms
+/// which must all match, each being a glob for the instance name, or `loader:<glob>` / `version:<glob>` /
+/// `group:<glob>`, where instances without a sync group have an empty group. Terms can be negated with `!`,
+/// e.g. `!loader:vanilla` only matches modded instances
+pub fn instance_matches_filter(filter: &str, name: &str, configuration: &InstanceConfiguration) -> bool {
+    filter.split_whitespace().all(|term| {
+        let (negated, term) = match term.strip_prefix('!') {
+            Some(term) => (true, term),
+            None => (false, term),
+        };
+
+        let matches = if let Some(pattern) = term.strip_prefix("loader:") {
+            glob_matches(pattern, configuration.loader.name())
+        } else if let Some(pattern) = term.strip_prefix("version:") {
+            glob_matches(pattern, configuration.minecraft_version.as_str())
+        } else if let Some(pattern) = term.strip_prefix("group:") {
+            glob_matches(pattern, configuration.sync_group.as_deref().unwrap_or_default())
+        } else {
+            glob_matches(term, name)
+        };
+
+        matches != negated
     })
 }
 
+/// Case-insensitive glob supporting `*` and `?`
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let mut pattern_index = 0;
+    let mut text_index = 0;
+    let mut backtrack = None;
+
+    while text_index < text.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, text_index));
+                pattern_index += 1;
+            },
+            Some(&c) if c == '?' || c == text[text_index] => {
+                pattern_index += 1;
+                text_index += 1;
+            },
+            _ => {
+                let Some((star_index, star_text_index)) = backtrack else {
+                    return false;
+                };
+                pattern_index = star_index + 1;
+                text_index = star_text_index + 1;
+                backtrack = Some((star_index, star_text_index + 1));
+            },
+        }
+    }
+
+    pattern[pattern_index..].iter().all(|c| *c == '*')
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct ManifestEntry {
+    size: u64,
+    sha1: String,
+}
+
+const MANIFESTS_DIR: &str = ".manifests";
+
+/// Total size and latest modification time of a file or everything inside a folder
+fn measure_synced_copy(path: &Path) -> (u64, Option<SystemTime>) {
+    let mut total_bytes = 0;
+    let mut last_modified = None;
+    for entry in walkdir::WalkDir::new(path).into_iter().flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_file() && is_ignored_file(entry.path()) {
+            continue;
+        }
+        if metadata.is_file() {
+            total_bytes += metadata.len();
+        }
+        if let Ok(modified) = metadata.modified() {
+            last_modified = last_modified.max(Some(modified));
+        }
+    }
+    (total_bytes, last_modified)
+}
+
+fn manifest_path(safe_path: &SafePath, directories: &LauncherDirectories) -> PathBuf {
+    let mut path = safe_path.to_path(&directories.synced_dir.join(MANIFESTS_DIR)).into_os_string();
+    path.push(".json");
+    path.into()
+}
+
+fn compute_manifest(target_dir: &Path) -> BTreeMap<String, ManifestEntry> {
+    let mut manifest = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(target_dir).into_iter().flatten() {
+        if !entry.file_type().is_file() || is_ignored_file(entry.path()) {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(target_dir) else {
+            continue;
+        };
+        let Ok(mut file) = std::fs::File::open(entry.path()) else {
+            continue;
+        };
+        let mut hasher = Sha1::new();
+        let Ok(size) = std::io::copy(&mut file, &mut hasher) else {
+            continue;
+        };
+        manifest.insert(relative.to_string_lossy().replace('\\', "/"), ManifestEntry {
+            size,
+            sha1: hex::encode(hasher.finalize()),
+        });
+    }
+    manifest
+}
+
+/// Records the checksums of every file currently in the store folder for `target`
+pub fn update_manifest(target: &str, directories: &LauncherDirectories) {
+    let Some(safe_path) = SafePath::new(target) else {
+        return;
+    };
+    let manifest = compute_manifest(&safe_path.to_path(&directories.synced_dir));
+    let path = manifest_path(&safe_path, directories);
+    _ = std::fs::create_dir_all(path.parent().unwrap());
+    match serde_json::to_vec(&manifest) {
+        Ok(bytes) => {
+            if let Err(error) = crate::write_safe(&path, &bytes) {
+                log::error!("Unable to write manifest for {}: {error}", target);
+            }
+        },
+        Err(error) => log::error!("Unable to serialize manifest for {}: {error}", target),
+    }
+}
+
+pub fn remove_manifest(target: &str, directories: &LauncherDirectories) {
+    if let Some(safe_path) = SafePath::new(target) {
+        _ = std::fs::remove_file(manifest_path(&safe_path, directories));
+    }
+}
+
+pub fn create_missing_manifests(config: &BackendConfig, directories: &LauncherDirectories) {
+    for target in config.verified_sync_targets.iter() {
+        if !config.sync_targets.folders.contains(target) {
+            continue;
+        }
+        if let Some(safe_path) = SafePath::new(target) && !manifest_path(&safe_path, directories).exists() {
+            update_manifest(target, directories);
+        }
+    }
+}
+
+fn find_modified_files(safe_path: &SafePath, target_dir: &Path, directories: &LauncherDirectories) -> Vec<Arc<str>> {
+    let Ok(expected) = crate::read_json::<BTreeMap<String, ManifestEntry>>(&manifest_path(safe_path, directories)) else {
+        return Vec::new();
+    };
+    let actual = compute_manifest(target_dir);
+
+    let mut modified = Vec::new();
+    for (name, entry) in &expected {
+        if actual.get(name) != Some(entry) {
+            modified.push(name.as_str().into());
+        }
+    }
+    for name in actual.keys() {
+        if !expected.contains_key(name) {
+            modified.push(name.as_str().into());
+        }
+    }
+    modified
+}
+
+pub fn get_sync_config_summary(config: &BackendConfig, instances: &mut BackendStateInstances) -> String {
+    use std::fmt::Write;
+
+    fn join<'a>(names: impl Iterator<Item = &'a str>) -> String {
+        let joined = names.collect::<Vec<_>>().join(", ");
+        if joined.is_empty() {
+            "none".into()
+        } else {
+            joined
+        }
+    }
+
+    let mut summary = String::from("Pandora sync setup\n");
+    _ = writeln!(summary, "Files: {}", join(config.sync_targets.files.iter().map(|name| &**name)));
+    _ = writeln!(summary, "Folders: {}", join(config.sync_targets.folders.iter().map(|name| &**name)));
+    _ = writeln!(summary, "Instance settings: {}", join(config.synced_instance_settings.iter().map(|setting| match setting {
+        SyncedInstanceSetting::JvmFlags => "JVM flags",
+        SyncedInstanceSetting::JvmBinary => "Java binary",
+        SyncedInstanceSetting::WrapperCommand => "wrapper command",
+        SyncedInstanceSetting::LinuxWrapper => "Linux wrapper",
+        SyncedInstanceSetting::SystemLibraries => "system libraries",
+    })));
+
+    let mut syncing_count = 0;
+    let mut disabled = Vec::new();
+    let mut exclusions = Vec::new();
+    for instance in instances.instances.iter_mut() {
+        let configuration = instance.configuration.get();
+        if configuration.disable_file_syncing {
+            disabled.push(instance.name);
+            continue;
+        }
+        syncing_count += 1;
+        if !configuration.sync_exclusions.is_empty() {
+            exclusions.push((instance.name, join(configuration.sync_exclusions.iter().map(|name| &**name))));
+        }
+    }
+
+    _ = write!(summary, "Instances: {} syncing", syncing_count);
+    if disabled.is_empty() {
+        summary.push('\n');
+    } else {
+        _ = writeln!(summary, ", syncing disabled for {}", join(disabled.iter().map(|name| name.as_str())));
+    }
+    for (name, excluded) in exclusions {
+        _ = writeln!(summary, "  {} excludes {}", name, excluded);
+    }
+
+    summary
+}
+
+fn synced_instance_settings_path(directories: &LauncherDirectories) -> PathBuf {
+    directories.synced_dir.join("instance_settings.json")
+}
+
+/// Copies the settings shared by the instances in `group` into `configuration`, or by the ones without a group if None
+pub fn apply_synced_instance_settings(settings: EnumSet<SyncedInstanceSetting>, group: Option<&str>, directories: &LauncherDirectories, configuration: &mut InstanceConfiguration) {
+    let path = synced_instance_settings_path(directories);
+    let mut shared: SharedInstanceSettings = crate::read_json(&path).unwrap_or_default();
+    if shared.group_mut(group).apply_to(settings, configuration) {
+        write_synced_instance_settings(&path, &shared);
+    }
+}
+
+pub fn store_synced_instance_settings(settings: EnumSet<SyncedInstanceSetting>, group: Option<&str>, directories: &LauncherDirectories, configuration: &InstanceConfiguration) {
+    let path = synced_instance_settings_path(directories);
+    let mut shared: SharedInstanceSettings = crate::read_json(&path).unwrap_or_default();
+    shared.group_mut(group).update_from(settings, configuration);
+    write_synced_instance_settings(&path, &shared);
+}
+
+fn write_synced_instance_settings(path: &Path, shared: &SharedInstanceSettings) {
+    _ = std::fs::create_dir_all(path.parent().unwrap());
+    match serde_json::to_vec_pretty(shared) {
+        Ok(bytes) => {
+            if let Err(error) = crate::write_safe(path, &bytes) {
+                log::error!("Unable to write synced instance settings: {error}");
+            }
+        },
+        Err(error) => log::error!("Unable to serialize synced instance settings: {error}"),
+    }
+}
+
+/// The user's patterns from BackendConfig::sync_ignore_patterns. Kept globally because the copying in `linking`
+/// happens far from anything that has the config
+static IGNORE_PATTERNS: Lazy<RwLock<Vec<Arc<str>>>> = Lazy::new(Default::default);
+
+pub fn set_ignore_patterns(patterns: &[Arc<str>]) {
+    *IGNORE_PATTERNS.write() = patterns.to_vec();
+}
+
+/// Whether a file is OS clutter such as .DS_Store that shouldn't be synced, copied or counted
+pub fn is_ignored_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    DEFAULT_SYNC_IGNORE_PATTERNS.iter().any(|pattern| glob_matches(pattern, name))
+        || IGNORE_PATTERNS.read().iter().any(|pattern| glob_matches(pattern, name))
+}
+
+/// Where SyncProgress goes, left unset when there's no frontend such as in the sync simulation
+static PROGRESS_SENDER: OnceCell<FrontendHandle> = OnceCell::new();
+
+/// How often SyncProgress is sent at most, so that placing thousands of small files doesn't flood the channel
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+thread_local! {
+    /// The operation currently counting steps on this thread. Like IGNORE_PATTERNS it can't be passed
+    /// down, since `linking` places the files
+    static CURRENT_PROGRESS: RefCell<Option<ProgressTracker>> = const { RefCell::new(None) };
+    /// Set while syncing something other than the real launcher folder, e.g. a simulation
+    static PROGRESS_SILENCED: Cell<bool> = const { Cell::new(false) };
+}
+
+struct ProgressTracker {
+    target: Arc<str>,
+    done: usize,
+    total: usize,
+    last_sent: Option<Instant>,
+    started: Instant,
+}
+
+pub fn set_progress_sender(send: FrontendHandle) {
+    _ = PROGRESS_SENDER.set(send);
+}
+
+/// Runs `f` without sending any progress to the frontend from this thread
+pub(crate) fn without_progress<T>(f: impl FnOnce() -> T) -> T {
+    with_progress_silenced(true, f)
+}
+
+fn with_progress_silenced<T>(silenced: bool, f: impl FnOnce() -> T) -> T {
+    let previous = PROGRESS_SILENCED.replace(silenced);
+    let result = f();
+    PROGRESS_SILENCED.set(previous);
+    result
+}
+
+/// Starts counting the steps of an operation on `target`, replacing any operation that stopped without finishing
+fn start_progress(target: &str, total: usize) {
+    if PROGRESS_SILENCED.get() {
+        CURRENT_PROGRESS.set(None);
+        return;
+    }
+    CURRENT_PROGRESS.set(Some(ProgressTracker {
+        target: target.into(),
+        done: 0,
+        total,
+        last_sent: None,
+        started: Instant::now(),
+    }));
+}
+
+fn advance_progress() {
+    CURRENT_PROGRESS.with_borrow_mut(|current| {
+        let Some(progress) = current else {
+            return;
+        };
+        // The total is an estimate, e.g. merging can add files after it was counted
+        progress.done = (progress.done + 1).min(progress.total);
+
+        // Operations that finish before the first interval never send anything
+        let since = progress.last_sent.unwrap_or(progress.started);
+        if since.elapsed() >= PROGRESS_INTERVAL {
+            progress.last_sent = Some(Instant::now());
+            send_progress(&progress.target, progress.done, progress.total);
+        }
+    });
+}
+
+fn finish_progress() {
+    if let Some(progress) = CURRENT_PROGRESS.take() && progress.last_sent.is_some() {
+        send_progress(&progress.target, progress.total, progress.total);
+    }
+}
+
+struct FinishProgressOnDrop;
+
+impl Drop for FinishProgressOnDrop {
+    fn drop(&mut self) {
+        finish_progress();
+    }
+}
+
+fn send_progress(target: &Arc<str>, done: usize, total: usize) {
+    if let Some(send) = PROGRESS_SENDER.get() {
+        send.send(MessageToFrontend::SyncProgress { target: target.clone(), done, total });
+    }
+}
+
+/// How many files copying `path` with the Hardlink or Copy strategies places
+fn count_synced_files(path: &Path) -> usize {
+    walkdir::WalkDir::new(path).into_iter().flatten()
+        .filter(|entry| entry.file_type().is_file() && !is_ignored_file(entry.path()))
+        .count()
+}
+
 static DEFAULT_FOLDERS: Lazy<Vec<Arc<str>>> = Lazy::new(|| {
     [
         "saves",
@@ -322,126 +1574,2230 @@ static DEFAULT_FOLDERS: Lazy<Vec<Arc<str>>> = Lazy::new(|| {
     ].into_iter().map(Arc::from).collect()
 });
 
-pub fn enable_all(name: &str, is_file: bool, instances: &mut BackendStateInstances, directories: &LauncherDirectories) -> std::io::Result<bool> {
-    if is_file {
-        return Ok(true);
-    }
-
-    let Some(safe_path) = SafePath::new(name) else {
-        log::warn!("Skipping folder sync because it is not a safe path: {}", name);
-        return Ok(false);
-    };
+pub struct MergedSyncTarget {
+    pub from: Arc<str>,
+    pub into: Arc<str>,
+    pub is_file: bool,
+}
 
-    let mut paths = Vec::new();
-    for instance in instances.instances.iter_mut() {
-        if !instance.configuration.get().disable_file_syncing {
-            paths.push(safe_path.to_path(&instance.dot_minecraft_path));
-        }
+/// Merges targets that refer to the same path (e.g. `config/` and `config`, or
+/// `Config` and `config` on case-insensitive platforms) into a single target.
+/// A file target with the same name as a folder target is merged into the folder
+pub fn reconcile_sync_targets(sync_targets: &mut SyncTargets) -> Vec<MergedSyncTarget> {
+    let mut merged = Vec::new();
+    reconcile_sync_target_set(&mut sync_targets.files, true, &mut merged);
+    reconcile_sync_target_set(&mut sync_targets.folders, false, &mut merged);
+
+    // Both would live at the same path in the synced folder, so a name can only be synced as one of them.
+    // The folder is kept as it usually holds a lot more than the file did
+    let shadowed: Vec<Arc<str>> = sync_targets.files.intersection(&sync_targets.folders).cloned().collect();
+    for name in shadowed {
+        sync_targets.files.remove(&name);
+        merged.push(MergedSyncTarget { from: name.clone(), into: name, is_file: true });
     }
 
-    let target_dir = safe_path.to_path(&directories.synced_dir);
-
-    // Exclude links that already point to target_dir
-    paths.retain(|path| {
-        !linking::is_targeting(&target_dir, &path)
-    });
+    merged
+}
 
-    for path in &paths {
-        if path.exists() {
-            return Ok(false);
-        }
+/// Carries the filter and verified state of target `from` over to `into`, for renames and merged targets.
+/// If `into` already has a filter of its own that one is kept
+pub fn move_target_settings(config: &mut BackendConfig, from: &Arc<str>, into: &Arc<str>) {
+    if from == into {
+        return;
     }
-
-    std::fs::create_dir_all(&target_dir)?;
-    for path in &paths {
-        if let Some(parent) = path.parent() {
-            _ = std::fs::create_dir_all(parent);
-        }
-        linking::link_dir(&target_dir, path)?;
+    if let Some(filter) = config.sync_target_filters.remove(from) {
+        config.sync_target_filters.entry(into.clone()).or_insert(filter);
+    }
+    if config.verified_sync_targets.remove(from) {
+        config.verified_sync_targets.insert(into.clone());
     }
+}
 
-    Ok(true)
+fn canonical_target_name(name: &str) -> Option<Arc<str>> {
+    let safe_path = SafePath::new(&name.replace('\\', "/"))?;
+    Some(safe_path.as_str().into())
 }
 
-pub fn disable_all(name: &str, is_file: bool, directories: &LauncherDirectories) -> std::io::Result<()> {
-    if is_file {
-        return Ok(());
+/// Resolves the store folder of a target as far as the filesystem allows, so that targets
+/// reaching the same folder through symlinks or case differences compare equal
+fn store_identity(safe_path: &SafePath, directories: &LauncherDirectories) -> PathBuf {
+    let path = safe_path.to_path(&directories.synced_dir);
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
+    if is_case_insensitive(directories) {
+        path.to_string_lossy().to_lowercase().into()
+    } else {
+        path
     }
+}
 
-    let Some(safe_path) = SafePath::new(name) else {
-        log::warn!("Skipping folder sync because it is not a safe path: {}", name);
-        return Ok(());
+/// Whether the filesystem holding the launcher folder treats names that only differ by case as the same, checked by
+/// looking the synced folder up under a different case. Falls back to the platform default if it doesn't exist yet
+pub fn is_case_insensitive(directories: &LauncherDirectories) -> bool {
+    let default = cfg!(any(windows, target_os = "macos"));
+    let Some(name) = directories.synced_dir.file_name().and_then(|name| name.to_str()) else {
+        return default;
     };
-
-    let mut paths = Vec::new();
-    let read_dir = std::fs::read_dir(&directories.instances_dir)?;
-    for entry in read_dir {
-        paths.push(safe_path.to_path(&entry?.path().join(".minecraft")));
+    let swapped: String = name.chars().map(|c| if c.is_lowercase() { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() }).collect();
+    if swapped == name || !directories.synced_dir.exists() {
+        return default;
     }
+    directories.synced_dir.with_file_name(swapped).exists()
+}
 
-    let target_dir = safe_path.to_path(&directories.synced_dir);
+/// Makes sure the synced folder doesn't overlap the instances folder or an instance's .minecraft, e.g. because one of
+/// them is a link into the other. Syncing in that state would link the store into itself, and disabling a target
+/// would delete files it's still using
+pub fn check_synced_dir_location(directories: &LauncherDirectories, instances: &BackendStateInstances) -> std::io::Result<()> {
+    let resolve = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let overlaps = |a: &Path, b: &Path| a.starts_with(b) || b.starts_with(a);
+
+    let synced_dir = resolve(&directories.synced_dir);
+    let instances_dir = resolve(&directories.instances_dir);
+    if overlaps(&synced_dir, &instances_dir) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput,
+            format!("The synced folder {:?} and the instances folder {:?} overlap", synced_dir, instances_dir)));
+    }
 
-    for path in &paths {
-        linking::unlink_dir_if_targeting(&target_dir, path)?;
+    for instance in instances.instances.iter() {
+        let dot_minecraft = resolve(&instance.dot_minecraft_path);
+        if overlaps(&synced_dir, &dot_minecraft) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput,
+                format!("The synced folder {:?} overlaps with the .minecraft of {} ({:?})", synced_dir, instance.name, dot_minecraft)));
+        }
     }
 
     Ok(())
 }
 
-#[cfg(unix)]
-mod linking {
-    use std::path::Path;
+/// Finds another enabled target that only differs from `name` by case. Only meaningful where is_case_insensitive
+/// holds, elsewhere both names are separate files
+pub fn find_case_variant(name: &str, sync_targets: &SyncTargets) -> Option<Arc<str>> {
+    let lowercase = name.to_lowercase();
+    sync_targets.files.iter().chain(sync_targets.folders.iter())
+        .find(|other| &***other != name && other.to_lowercase() == lowercase)
+        .cloned()
+}
 
-    pub fn link_dir(original: &Path, link: &Path) -> std::io::Result<()> {
-        std::os::unix::fs::symlink(original, link)
-    }
+/// Finds another enabled folder target whose store folder is the same as the one for `name`
+pub fn find_store_alias(name: &str, sync_targets: &SyncTargets, directories: &LauncherDirectories) -> Option<Arc<str>> {
+    let identity = store_identity(&SafePath::new(name)?, directories);
+    sync_targets.folders.iter()
+        .filter(|other| &***other != name)
+        .find(|other| SafePath::new(other).is_some_and(|other| store_identity(&other, directories) == identity))
+        .cloned()
+}
 
-    pub fn is_targeting(original: &Path, link: &Path) -> bool {
-        let Ok(target) = std::fs::read_link(link) else {
-            return false;
+fn reconcile_sync_target_set(set: &mut BTreeSet<Arc<str>>, is_file: bool, merged: &mut Vec<MergedSyncTarget>) {
+    let mut by_key: BTreeMap<String, Vec<(Arc<str>, Arc<str>)>> = BTreeMap::new();
+    for name in set.iter() {
+        let Some(canonical) = canonical_target_name(name) else {
+            continue;
         };
-
-        target == original
+        let key = if cfg!(any(windows, target_os = "macos")) {
+            canonical.to_lowercase()
+        } else {
+            canonical.to_string()
+        };
+        by_key.entry(key).or_default().push((name.clone(), canonical));
     }
 
-    pub fn unlink_dir_if_targeting(original: &Path, link: &Path) -> std::io::Result<()> {
-        let Ok(target) = std::fs::read_link(link) else {
-            return Ok(());
-        };
+    for names in by_key.into_values() {
+        let into = names.iter()
+            .find(|(name, canonical)| name == canonical)
+            .map(|(name, _)| name.clone())
+            .unwrap_or_else(|| names[0].1.clone());
 
-        if target == original {
-            std::fs::remove_file(link)?;
+        for (name, _) in names {
+            if name != into {
+                set.remove(&name);
+                merged.push(MergedSyncTarget { from: name, into: into.clone(), is_file });
+            }
         }
-
-        Ok(())
+        set.insert(into);
     }
 }
 
-#[cfg(windows)]
-mod linking {
-    use std::path::Path;
-
-    pub fn link_dir(original: &Path, link: &Path) -> std::io::Result<()> {
-        junction::create(original, link)
+pub fn maintain_store(
+    sync_targets: &SyncTargets,
+    filters: &BTreeMap<Arc<str>, Arc<str>>,
+    link_strategy: LinkStrategy,
+    copy_fallback: bool,
+    backup_retention: Option<Duration>,
+    instances: &mut BackendStateInstances,
+    directories: &LauncherDirectories,
+) -> std::io::Result<StoreMaintenanceReport> {
+    let mut report = StoreMaintenanceReport::default();
+
+    std::fs::create_dir_all(&directories.synced_dir)?;
+
+    let mut used_names = FxHashSet::default();
+    used_names.insert("fallback_options.txt");
+    used_names.insert("instance_settings.json");
+    used_names.insert(CHECKPOINT_FILE);
+    used_names.insert(MANIFESTS_DIR);
+
+    // File targets keep a copy in the store too
+    for file_target in sync_targets.files.iter() {
+        if let Some(first) = file_target.split(['/', '\\']).find(|component| !component.is_empty() && *component != ".") {
+            used_names.insert(first);
+        }
     }
 
-    pub fn is_targeting(original: &Path, link: &Path) -> bool {
-        let Ok(target) = junction::get_target(link) else {
-            return false;
+    for folder_target in sync_targets.folders.iter() {
+        let Some(safe_path) = SafePath::new(folder_target) else {
+            report.problems.push(format!("'{}' is not a safe path", folder_target).into());
+            continue;
         };
 
-        target == original
-    }
-
-    pub fn unlink_dir_if_targeting(original: &Path, link: &Path) -> std::io::Result<()> {
-        let Ok(target) = junction::get_target(link) else {
-            return Ok(());
-        };
+        if let Some(first) = folder_target.split(['/', '\\']).find(|component| !component.is_empty() && *component != ".") {
+            used_names.insert(first);
+        }
 
-        if target == original {
-            junction::delete(link)?;
+        let target_dir = safe_path.to_path(&directories.synced_dir);
+        if !target_dir.exists() {
+            std::fs::create_dir_all(&target_dir)?;
+            report.actions.push(format!("Created missing synced folder for '{}'", folder_target).into());
+        } else if !target_dir.is_dir() {
+            report.problems.push(format!("Synced path for '{}' exists but is not a folder", folder_target).into());
+            continue;
         }
 
+        // Only the instances the target applies to are expected to link it, the same ones enable_all links
+        let mut dot_minecraft_paths = Vec::new();
+        for instance in instances.instances.iter_mut() {
+            let configuration = instance.configuration.get();
+            if configuration.disable_file_syncing || configuration.sync_exclusions.contains(&**folder_target) {
+                continue;
+            }
+            if let Some(filter) = filters.get(folder_target) && !instance_matches_filter(filter, &instance.name, configuration) {
+                continue;
+            }
+            let path = safe_path.to_path(&instance.dot_minecraft_path);
+            if covered_by_parent_link(&path, &instance.dot_minecraft_path, directories) {
+                continue;
+            }
+            dot_minecraft_paths.push(instance.dot_minecraft_path.clone());
+        }
+
+        for dot_minecraft in &dot_minecraft_paths {
+            let path = safe_path.to_path(dot_minecraft);
+
+            if linking::is_targeting(&target_dir, &path) {
+                report.verified_links += 1;
+            } else if !path.exists() && std::fs::symlink_metadata(&path).is_err() {
+                if let Some(parent) = path.parent() {
+                    _ = std::fs::create_dir_all(parent);
+                }
+                match linking::link_dir(&target_dir, &path, link_strategy, copy_fallback) {
+                    Ok(()) => {
+                        report.verified_links += 1;
+                        report.actions.push(format!("Linked missing '{}' folder in {:?}", folder_target, dot_minecraft).into());
+                    },
+                    Err(error) => {
+                        report.problems.push(format!("Unable to link '{}' in {:?}: {}", folder_target, dot_minecraft, error).into());
+                    },
+                }
+            } else {
+                report.problems.push(format!("'{}' in {:?} is not linked to the synced folder", folder_target, dot_minecraft).into());
+            }
+        }
+    }
+
+    for entry in std::fs::read_dir(&directories.synced_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        // Leftovers from an interrupted write_safe or reseed, nothing else in the store is removed by name
+        if file_name.starts_with(STAGING_PREFIX) || (file_type.is_file() && is_write_safe_temp(file_name)) {
+            let size = if file_type.is_dir() { dir_size(&entry.path()) } else { entry.metadata().map(|metadata| metadata.len()).unwrap_or(0) };
+            let removed = if file_type.is_dir() { std::fs::remove_dir_all(entry.path()) } else { std::fs::remove_file(entry.path()) };
+            if removed.is_ok() {
+                report.reclaimed_bytes += size;
+                report.actions.push(format!("Removed leftover temporary file '{}'", file_name).into());
+            }
+            continue;
+        }
+
+        if !file_type.is_dir() || used_names.contains(file_name) {
+            continue;
+        }
+
+        let is_empty = std::fs::read_dir(entry.path()).map(|mut read_dir| read_dir.next().is_none()).unwrap_or(false);
+        if is_empty {
+            if std::fs::remove_dir(entry.path()).is_ok() {
+                report.actions.push(format!("Removed empty unused folder '{}'", file_name).into());
+            }
+        } else {
+            report.problems.push(format!("Folder '{}' is not used by any enabled sync target", file_name).into());
+        }
+    }
+
+    if let Some(backup_retention) = backup_retention {
+        prune_conflict_backups(sync_targets, backup_retention, directories, &mut report);
+    }
+
+    Ok(report)
+}
+
+/// Whether `file_name` is one of the `<name>.<random>.new` files write_safe renames into place
+fn is_write_safe_temp(file_name: &str) -> bool {
+    let Some((name, random)) = file_name.strip_suffix(".new").and_then(|rest| rest.rsplit_once('.')) else {
+        return false;
+    };
+    !name.is_empty() && !random.is_empty() && random.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path).into_iter().flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Removes the .conflict copies kept while merging folders that haven't been touched for longer than `retention`
+fn prune_conflict_backups(sync_targets: &SyncTargets, retention: Duration, directories: &LauncherDirectories, report: &mut StoreMaintenanceReport) {
+    for folder_target in sync_targets.folders.iter() {
+        let Some(safe_path) = SafePath::new(folder_target) else {
+            continue;
+        };
+        let target_dir = safe_path.to_path(&directories.synced_dir);
+
+        for entry in walkdir::WalkDir::new(&target_dir).into_iter().flatten() {
+            if !entry.file_type().is_file() || !is_conflict_backup(entry.path()) {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let expired = metadata.modified().ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > retention);
+            if !expired {
+                continue;
+            }
+
+            let relative = entry.path().strip_prefix(&directories.synced_dir).unwrap_or(entry.path());
+            match std::fs::remove_file(entry.path()) {
+                Ok(()) => {
+                    report.reclaimed_bytes += metadata.len();
+                    report.actions.push(format!("Removed old conflict backup '{}'", relative.display()).into());
+                },
+                Err(error) => {
+                    report.problems.push(format!("Unable to remove old conflict backup '{}': {}", relative.display(), error).into());
+                },
+            }
+        }
+    }
+}
+
+/// Whether `path` was named by conflict_path, i.e. its extension is `conflict` optionally followed by a number
+fn is_conflict_backup(path: &Path) -> bool {
+    path.extension().and_then(|extension| extension.to_str())
+        .and_then(|extension| extension.strip_prefix("conflict"))
+        .is_some_and(|index| index.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+/// Progress of a bulk enable/disable, stored in the synced folder so that an
+/// interrupted operation can be resumed without redoing the instances that were already done
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncCheckpoint {
+    pub target: Arc<str>,
+    pub is_file: bool,
+    pub value: bool,
+    /// Whether existing instance folders should be merged into the synced folder instead of refusing to enable
+    #[serde(default)]
+    pub merge: bool,
+    #[serde(default)]
+    completed: BTreeSet<PathBuf>,
+    #[serde(default)]
+    conflicts: Vec<Arc<str>>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl SyncCheckpoint {
+    pub fn begin(target: Arc<str>, is_file: bool, value: bool, merge: bool, directories: &LauncherDirectories) -> Self {
+        let checkpoint = Self {
+            target,
+            is_file,
+            value,
+            merge,
+            completed: BTreeSet::new(),
+            conflicts: Vec::new(),
+            path: directories.synced_dir.join(CHECKPOINT_FILE),
+        };
+        checkpoint.save();
+        checkpoint
+    }
+
+    pub fn load(directories: &LauncherDirectories) -> Option<Self> {
+        let path = directories.synced_dir.join(CHECKPOINT_FILE);
+        if !path.exists() {
+            return None;
+        }
+
+        match crate::read_json::<Self>(&path) {
+            Ok(mut checkpoint) => {
+                checkpoint.path = path;
+                Some(checkpoint)
+            },
+            Err(error) => {
+                log::warn!("Discarding unreadable sync checkpoint: {error:?}");
+                _ = std::fs::remove_file(&path);
+                None
+            },
+        }
+    }
+
+    pub fn summary(&self) -> InterruptedSyncOperation {
+        InterruptedSyncOperation {
+            target: self.target.clone(),
+            is_file: self.is_file,
+            enabling: self.value,
+            completed_count: self.completed.len(),
+        }
+    }
+
+    /// Files that were kept with a .conflict suffix while merging, relative to the synced folder
+    pub fn conflicts(&self) -> &[Arc<str>] {
+        &self.conflicts
+    }
+
+    pub fn finish(self) {
+        _ = std::fs::remove_file(&self.path);
+    }
+
+    fn is_completed(&self, path: &Path) -> bool {
+        self.completed.contains(path)
+    }
+
+    fn complete(&mut self, path: PathBuf) {
+        self.completed.insert(path);
+        self.save();
+    }
+
+    fn save(&self) {
+        _ = std::fs::create_dir_all(self.path.parent().unwrap());
+        match serde_json::to_vec(self) {
+            Ok(bytes) => {
+                if let Err(error) = crate::write_safe(&self.path, &bytes) {
+                    log::error!("Unable to write sync checkpoint: {error}");
+                }
+            },
+            Err(error) => log::error!("Unable to serialize sync checkpoint: {error}"),
+        }
+    }
+}
+
+const CHECKPOINT_FILE: &str = ".sync_checkpoint.json";
+
+/// Files and folders directly inside the synced folder starting with this are temporary copies made while syncing,
+/// anything left over after a crash can be deleted
+const STAGING_PREFIX: &str = ".pandora_staging_";
+
+pub fn enable_all(name: &str, is_file: bool, filter: Option<&str>, link_strategy: LinkStrategy, copy_fallback: bool, checkpoint: &mut SyncCheckpoint, instances: &mut BackendStateInstances, directories: &LauncherDirectories) -> std::io::Result<bool> {
+    if is_file {
+        return Ok(true);
+    }
+
+    let Some(safe_path) = SafePath::new(name) else {
+        log::warn!("Skipping folder sync because it is not a safe path: {}", name);
+        return Ok(false);
+    };
+
+    let mut paths = Vec::new();
+    for instance in instances.instances.iter_mut() {
+        let configuration = instance.configuration.get();
+        if configuration.disable_file_syncing || configuration.sync_exclusions.contains(name) {
+            continue;
+        }
+        if let Some(filter) = filter && !instance_matches_filter(filter, &instance.name, configuration) {
+            continue;
+        }
+        let path = safe_path.to_path(&instance.dot_minecraft_path);
+        if covered_by_parent_link(&path, &instance.dot_minecraft_path, directories) {
+            continue;
+        }
+        paths.push(path);
+    }
+
+    let target_dir = safe_path.to_path(&directories.synced_dir);
+
+    // Exclude links that already point to target_dir
+    paths.retain(|path| {
+        !checkpoint.is_completed(path) && !linking::is_targeting(&target_dir, &path)
+    });
+
+    for path in &paths {
+        // Links elsewhere are never merged since moving their contents would empty a folder outside the instance.
+        // A folder that only holds links for nested targets is fine, those links are replaced by this one
+        if path.exists() && (!path.is_dir() || linking::link_target(path).is_some()
+            || (!checkpoint.merge && !only_contains_links_into(path, &target_dir)))
+        {
+            return Ok(false);
+        }
+    }
+
+    std::fs::create_dir_all(&target_dir)?;
+
+    // Links are made in an instant, the strategies that copy every file are what take long enough to show progress
+    let materialized = matches!(link_strategy, LinkStrategy::Hardlink | LinkStrategy::Copy);
+    let steps_per_path = if materialized { count_synced_files(&target_dir).max(1) } else { 1 };
+    start_progress(name, paths.len() * steps_per_path);
+    // Finishes the progress even when one of the paths below fails, so the frontend doesn't keep showing it
+    let _progress = FinishProgressOnDrop;
+
+    for path in paths {
+        if path.exists() {
+            remove_links_into(&path, &target_dir)?;
+            let conflicts = merge_into_store(&path, &target_dir)?;
+            checkpoint.conflicts.extend(conflicts.iter().map(|conflict| {
+                format!("{}/{}", name, conflict.to_string_lossy().replace('\\', "/")).into()
+            }));
+            std::fs::remove_dir_all(&path)?;
+        }
+        if let Some(parent) = path.parent() {
+            _ = std::fs::create_dir_all(parent);
+        }
+        linking::link_dir(&target_dir, &path, link_strategy, copy_fallback)?;
+        if !materialized {
+            advance_progress();
+        }
+        checkpoint.complete(path);
+    }
+
+    Ok(true)
+}
+
+/// Makes one instance's copy of a target the synced copy, replacing what was in the store. Instances that link
+/// to the store see the new contents straight away, the caller pushes files out to the other instances
+pub fn push_from_instance(name: &str, is_file: bool, dot_minecraft: &Path, link_strategy: LinkStrategy, copy_fallback: bool, instances: &mut BackendStateInstances, directories: &LauncherDirectories) -> std::io::Result<()> {
+    let Some(safe_path) = SafePath::new(name) else {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("'{}' is not a safe path", name)));
+    };
+    let source = safe_path.to_path(dot_minecraft);
+
+    if is_file {
+        if !source.is_file() {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("The instance doesn't have a '{}' file", name)));
+        }
+        copy_file_if_different(&source, &store_file_path(&safe_path, directories))?;
+        return Ok(());
+    }
+
+    let target_dir = safe_path.to_path(&directories.synced_dir);
+    let materialized = linking::materialized_target(&source).is_some_and(|target| target == target_dir);
+    match linking::link_target(&source) {
+        // The instance is already looking at the store
+        Some(target) if target == target_dir && !materialized => return Ok(()),
+        Some(target) if target != target_dir => {
+            return Err(std::io::Error::other(format!("The instance's '{}' folder is linked to {:?}", name, target)));
+        },
+        _ => {},
+    }
+    if !source.is_dir() {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("The instance doesn't have a '{}' folder", name)));
+    }
+
+    // Folders created by the Hardlink and Copy strategies still hold the old contents, so they're recreated below.
+    // Changes made in them since the last sync would be lost, so those instances have to be synced first
+    let mut materialized_copies = Vec::new();
+    for instance in instances.instances.iter_mut() {
+        let path = safe_path.to_path(&instance.dot_minecraft_path);
+        if linking::materialized_target(&path).is_some_and(|target| target == target_dir) {
+            if path != source && linking::has_drifted(&target_dir, &path) {
+                return Err(std::io::Error::other(format!("{} has changes in its '{}' folder that haven't been synced yet, apply syncing first", instance.name, name)));
+            }
+            materialized_copies.push(path);
+        }
+    }
+
+    // Staged inside the store rather than the temp folder, renaming across drives would fail
+    let id = rand::thread_rng().next_u64();
+    let staging = directories.synced_dir.join(format!("{}reseed_{}", STAGING_PREFIX, id));
+    let previous = directories.synced_dir.join(format!("{}previous_{}", STAGING_PREFIX, id));
+    linking::copy_contents(&source, &staging)?;
+
+    if target_dir.exists() {
+        std::fs::rename(&target_dir, &previous)?;
+    } else if let Some(parent) = target_dir.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if let Err(error) = std::fs::rename(&staging, &target_dir) {
+        _ = std::fs::rename(&previous, &target_dir);
+        _ = std::fs::remove_dir_all(&staging);
+        return Err(error);
+    }
+    _ = std::fs::remove_dir_all(&previous);
+
+    for path in materialized_copies {
+        std::fs::remove_dir_all(&path)?;
+        linking::link_dir(&target_dir, &path, link_strategy, copy_fallback)?;
+    }
+
+    Ok(())
+}
+
+/// Moves the synced copy of a target to a new name and links every instance that was linked to the old name to the new
+/// one instead. Instance copies of file targets are left alone, the next sync writes them under the new name
+pub fn rename_target(old: &str, new: &str, is_file: bool, link_strategy: LinkStrategy, copy_fallback: bool, instances: &mut BackendStateInstances, directories: &LauncherDirectories) -> std::io::Result<()> {
+    let (Some(old_path), Some(new_path)) = (SafePath::new(old), SafePath::new(new)) else {
+        // Patterns have no single synced copy, the files they matched keep their names
+        if is_file && (is_path_pattern(old) || is_path_pattern(new)) {
+            return Ok(());
+        }
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("'{}' is not a safe path", new)));
+    };
+    // Names that only differ by case are the same file where the filesystem ignores case
+    let renames_itself = store_identity(&old_path, directories) == store_identity(&new_path, directories);
+
+    if is_file {
+        let from = store_file_path(&old_path, directories);
+        let to = store_file_path(&new_path, directories);
+        if !renames_itself && std::fs::symlink_metadata(&to).is_ok() {
+            return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("The synced folder already has a '{}' file", new)));
+        }
+        if from.exists() {
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(&from, &to)?;
+        }
+        return Ok(());
+    }
+
+    let old_dir = old_path.to_path(&directories.synced_dir);
+    let new_dir = new_path.to_path(&directories.synced_dir);
+    if !renames_itself && std::fs::symlink_metadata(&new_dir).is_ok() {
+        return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("The synced folder already has a '{}' folder", new)));
+    }
+
+    // Checked for every instance first so that a refusal doesn't leave some of them unlinked
+    let mut links = Vec::new();
+    for instance in instances.instances.iter_mut() {
+        let link = old_path.to_path(&instance.dot_minecraft_path);
+        if !linking::is_targeting(&old_dir, &link) {
+            continue;
+        }
+        let renamed_link = new_path.to_path(&instance.dot_minecraft_path);
+        if std::fs::symlink_metadata(&renamed_link).is_ok() && !linking::is_targeting(&old_dir, &renamed_link) {
+            return Err(std::io::Error::new(std::io::ErrorKind::AlreadyExists, format!("{} already has its own '{}' folder", instance.name, new)));
+        }
+        links.push((link, renamed_link));
+    }
+
+    for (link, _) in &links {
+        linking::unlink_dir_if_targeting(&old_dir, link)?;
+    }
+
+    let moved = if old_dir.exists() {
+        new_dir.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| std::fs::rename(&old_dir, &new_dir))
+    } else {
+        std::fs::create_dir_all(&new_dir)
+    };
+    if let Err(error) = moved {
+        for (link, _) in &links {
+            _ = linking::link_dir(&old_dir, link, link_strategy, copy_fallback);
+        }
+        return Err(error);
+    }
+
+    let old_manifest = manifest_path(&old_path, directories);
+    if old_manifest.exists() {
+        let new_manifest = manifest_path(&new_path, directories);
+        _ = std::fs::create_dir_all(new_manifest.parent().unwrap());
+        _ = std::fs::rename(&old_manifest, &new_manifest);
+    }
+    if let Some(parent) = old_dir.parent() {
+        remove_empty_store_dirs(parent, directories);
+    }
+
+    for (_, renamed_link) in links {
+        if let Some(parent) = renamed_link.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        linking::link_dir(&new_dir, &renamed_link, link_strategy, copy_fallback)?;
+    }
+
+    Ok(())
+}
+
+/// Whether `path` has no files apart from those inside links to folders within `target_dir`
+fn only_contains_links_into(path: &Path, target_dir: &Path) -> bool {
+    let mut dir_iterator = walkdir::WalkDir::new(path).min_depth(1).into_iter();
+    while let Some(entry) = dir_iterator.next() {
+        let Ok(entry) = entry else {
+            return false;
+        };
+        if linking::link_target(entry.path()).is_some_and(|target| target.starts_with(target_dir)) {
+            if entry.file_type().is_dir() {
+                dir_iterator.skip_current_dir();
+            }
+            continue;
+        }
+        if !entry.file_type().is_dir() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Unlinks every link inside `path` that points to a folder within `target_dir`
+fn remove_links_into(path: &Path, target_dir: &Path) -> std::io::Result<()> {
+    let mut links = Vec::new();
+    let mut dir_iterator = walkdir::WalkDir::new(path).min_depth(1).into_iter();
+    while let Some(entry) = dir_iterator.next() {
+        let entry = entry?;
+        if let Some(target) = linking::link_target(entry.path()) && target.starts_with(target_dir) {
+            if entry.file_type().is_dir() {
+                dir_iterator.skip_current_dir();
+            }
+            links.push((target, entry.into_path()));
+        }
+    }
+
+    for (target, link) in links {
+        linking::unlink_dir_if_targeting(&target, &link)?;
+    }
+    Ok(())
+}
+
+/// Moves everything in `source` into `target`. Files that already exist in `target` with different contents
+/// are kept next to the existing file with a .conflict suffix, and their paths relative to `target` are returned
+fn merge_into_store(source: &Path, target: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut conflicts = Vec::new();
+
+    for entry in walkdir::WalkDir::new(source).min_depth(1) {
+        let entry = entry?;
+        let Ok(relative) = entry.path().strip_prefix(source) else {
+            continue;
+        };
+
+        let mut destination = target.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&destination)?;
+            continue;
+        }
+        // Left behind with the rest of the instance folder instead of ending up in every instance
+        if is_ignored_file(entry.path()) {
+            continue;
+        }
+
+        if std::fs::symlink_metadata(&destination).is_ok() {
+            if !files_differ(entry.path(), &destination) {
+                continue;
+            }
+            destination = conflict_path(&destination);
+            conflicts.push(relative.with_file_name(destination.file_name().unwrap()));
+        }
+
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if std::fs::rename(entry.path(), &destination).is_err() {
+            // Renaming fails across filesystems
+            copy_keeping_metadata(entry.path(), &destination)?;
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+
+    Ok(conflicts)
+}
+
+fn conflict_path(path: &Path) -> PathBuf {
+    let mut suffix = String::from(".conflict");
+    let mut index = 1;
+    loop {
+        let mut candidate = path.as_os_str().to_owned();
+        candidate.push(&suffix);
+        let candidate = PathBuf::from(candidate);
+        if std::fs::symlink_metadata(&candidate).is_err() {
+            return candidate;
+        }
+        index += 1;
+        suffix = format!(".conflict{}", index);
+    }
+}
+
+pub fn disable_all(name: &str, is_file: bool, checkpoint: &mut SyncCheckpoint, directories: &LauncherDirectories) -> std::io::Result<()> {
+    if is_file {
+        return Ok(());
+    }
+    disable_all_in(name, is_file, &list_dot_minecraft_dirs(directories)?, checkpoint, directories)
+}
+
+/// The .minecraft folder of every folder in the instances folder, including ones that aren't loaded as instances
+pub fn list_dot_minecraft_dirs(directories: &LauncherDirectories) -> std::io::Result<Vec<PathBuf>> {
+    let mut dot_minecraft_dirs = Vec::new();
+    for entry in std::fs::read_dir(&directories.instances_dir)? {
+        dot_minecraft_dirs.push(entry?.path().join(".minecraft"));
+    }
+    Ok(dot_minecraft_dirs)
+}
+
+/// disable_all for the instances in `dot_minecraft_dirs`, so that disabling several targets in a row
+/// only has to list the instances folder once
+pub fn disable_all_in(name: &str, is_file: bool, dot_minecraft_dirs: &[PathBuf], checkpoint: &mut SyncCheckpoint, directories: &LauncherDirectories) -> std::io::Result<()> {
+    if is_file {
+        return Ok(());
+    }
+
+    let Some(safe_path) = SafePath::new(name) else {
+        log::warn!("Skipping folder sync because it is not a safe path: {}", name);
+        return Ok(());
+    };
+
+    let target_dir = safe_path.to_path(&directories.synced_dir);
+
+    let mut paths = Vec::new();
+    // Folders inside an enabled parent target are still visible to instances through its link
+    let mut still_visible = false;
+    for dot_minecraft in dot_minecraft_dirs {
+        let path = safe_path.to_path(dot_minecraft);
+        still_visible |= covered_by_parent_link(&path, dot_minecraft, directories);
+        paths.push(path);
+    }
+
+    for path in paths {
+        if checkpoint.is_completed(&path) {
+            continue;
+        }
+        linking::unlink_dir_if_targeting(&target_dir, &path)?;
+        checkpoint.complete(path);
+    }
+
+    if !still_visible {
+        remove_empty_store_dirs(&target_dir, directories);
+    }
+
+    Ok(())
+}
+
+/// Removes `target_dir` and then its parents inside the store for as long as they are empty
+fn remove_empty_store_dirs(target_dir: &Path, directories: &LauncherDirectories) {
+    let mut current = Some(target_dir);
+    while let Some(dir) = current && dir != &*directories.synced_dir && dir.starts_with(&directories.synced_dir) {
+        // remove_dir only succeeds on empty folders
+        if std::fs::remove_dir(dir).is_err() {
+            break;
+        }
+        log::info!("Removed empty synced folder {:?}", dir);
+        current = dir.parent();
+    }
+}
+
+/// Lists the instance folders that disable_all would unlink, along with real folders it would leave alone
+pub fn preview_disable(name: &str, directories: &LauncherDirectories) -> std::io::Result<DisablePreview> {
+    let mut preview = DisablePreview::default();
+
+    let Some(safe_path) = SafePath::new(name) else {
+        return Ok(preview);
+    };
+    let target_dir = safe_path.to_path(&directories.synced_dir);
+
+    let read_dir = std::fs::read_dir(&directories.instances_dir)?;
+    for entry in read_dir {
+        let entry = entry?;
+        let path = safe_path.to_path(&entry.path().join(".minecraft"));
+        let instance: Arc<str> = entry.file_name().to_string_lossy().into();
+
+        if linking::is_targeting(&target_dir, &path) {
+            preview.links.push(instance);
+        } else if path.exists() {
+            preview.untouched.push(instance);
+        }
+    }
+
+    preview.links.sort();
+    preview.untouched.sort();
+    Ok(preview)
+}
+
+mod linking {
+    use std::{collections::BTreeMap, path::{Path, PathBuf}, time::SystemTime};
+
+    use schema::backend_config::LinkStrategy;
+
+    /// Written inside folders created by the Hardlink and Copy strategies so they can be recognised
+    /// later. The first line is the strategy and the second line is the synced folder
+    const MARKER_FILE: &str = ".pandora_synced_folder";
+
+    /// Why a synced folder couldn't be linked into an instance
+    #[derive(Debug)]
+    pub enum LinkError {
+        /// Creating symlinks on Windows needs Developer Mode or an elevated launcher
+        NeedsPrivilege(std::io::Error),
+        /// Some setups can't link across drives, e.g. an instance on a network share
+        CrossesDevices(std::io::Error),
+        Io(std::io::Error),
+    }
+
+    impl LinkError {
+        /// Picks out the errors of creating a link that copying the folder instead gets around
+        pub fn from_link_error(error: std::io::Error) -> Self {
+            if is_missing_privilege(&error) {
+                LinkError::NeedsPrivilege(error)
+            } else if error.kind() == std::io::ErrorKind::CrossesDevices {
+                LinkError::CrossesDevices(error)
+            } else {
+                LinkError::Io(error)
+            }
+        }
+    }
+
+    impl From<std::io::Error> for LinkError {
+        fn from(error: std::io::Error) -> Self {
+            LinkError::Io(error)
+        }
+    }
+
+    impl std::fmt::Display for LinkError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                LinkError::NeedsPrivilege(error) => write!(f, "{}. {}", error, PRIVILEGE_HINT),
+                LinkError::CrossesDevices(error) => write!(f, "{}. The instance is on a different drive than the synced folder, \
+                    enable copying folders that can't be linked or use the Copy link strategy", error),
+                LinkError::Io(error) => error.fmt(f),
+            }
+        }
+    }
+
+    impl std::error::Error for LinkError {}
+
+    impl From<LinkError> for std::io::Error {
+        fn from(error: LinkError) -> Self {
+            match error {
+                LinkError::NeedsPrivilege(_) => std::io::Error::new(std::io::ErrorKind::PermissionDenied, error.to_string()),
+                LinkError::CrossesDevices(_) => std::io::Error::new(std::io::ErrorKind::CrossesDevices, error.to_string()),
+                LinkError::Io(error) => error,
+            }
+        }
+    }
+
+    const PRIVILEGE_HINT: &str = "Enable Developer Mode in the Windows settings or run the launcher as administrator once, \
+        or switch to the Junction or Copy link strategy";
+
+    /// Links `link` to `original` with `strategy`. With `copy_fallback` a folder that can't be linked, because the
+    /// launcher isn't allowed to or it's on another drive, is copied instead, which keeps it syncing every launch
+    pub fn link_dir(original: &Path, link: &Path, strategy: LinkStrategy, copy_fallback: bool) -> Result<(), LinkError> {
+        match strategy {
+            LinkStrategy::Symlink => link_or_copy(original, link, copy_fallback, symlink_dir),
+            LinkStrategy::Junction => link_or_copy(original, link, copy_fallback, junction_dir),
+            // Files that can't be hardlinked, e.g. because they are on another drive, are copied by place_file
+            LinkStrategy::Hardlink => Ok(materialize_dir(original, link, true)?),
+            LinkStrategy::Copy => Ok(materialize_dir(original, link, false)?),
+        }
+    }
+
+    fn link_or_copy(original: &Path, link: &Path, copy_fallback: bool, create_link: impl FnOnce(&Path, &Path) -> std::io::Result<()>) -> Result<(), LinkError> {
+        let Err(error) = create_link(original, link) else {
+            return Ok(());
+        };
+
+        match LinkError::from_link_error(error) {
+            // The error explains how to get links working in case copying doesn't work either
+            error @ (LinkError::NeedsPrivilege(_) | LinkError::CrossesDevices(_)) if copy_fallback && !link.exists() => {
+                log::warn!("Copying {:?} into {:?} instead of linking: {}", original, link, error);
+                materialize_dir(original, link, false).map_err(|copy_error| {
+                    LinkError::Io(std::io::Error::new(copy_error.kind(), format!("{}, and copying failed too: {}", error, copy_error)))
+                })
+            },
+            error => Err(error),
+        }
+    }
+
+    /// ERROR_PRIVILEGE_NOT_HELD, returned when creating a symlink without SeCreateSymbolicLinkPrivilege
+    #[cfg(windows)]
+    const ERROR_PRIVILEGE_NOT_HELD: i32 = 1314;
+
+    /// ERROR_ACCESS_DENIED, which some Windows versions return instead when the link can't be created
+    #[cfg(windows)]
+    const ERROR_ACCESS_DENIED: i32 = 5;
+
+    #[cfg(windows)]
+    fn is_missing_privilege(error: &std::io::Error) -> bool {
+        matches!(error.raw_os_error(), Some(ERROR_PRIVILEGE_NOT_HELD | ERROR_ACCESS_DENIED))
+    }
+
+    #[cfg(not(windows))]
+    fn is_missing_privilege(_error: &std::io::Error) -> bool {
+        false
+    }
+
+    fn materialize_dir(original: &Path, link: &Path, hardlink: bool) -> std::io::Result<()> {
+        std::fs::create_dir(link)?;
+        let kind = if hardlink { "hardlink" } else { "copy" };
+        std::fs::write(link.join(MARKER_FILE), format!("{}\n{}", kind, original.to_string_lossy()))?;
+        mirror(original, link, hardlink)
+    }
+
+    /// Returns the synced folder that `link` points to, regardless of which strategy created it
+    pub fn link_target(link: &Path) -> Option<PathBuf> {
+        #[cfg(windows)]
+        if let Ok(target) = junction::get_target(link) {
+            return Some(target);
+        }
+
+        if let Ok(target) = std::fs::read_link(link) {
+            return Some(target);
+        }
+
+        read_marker(link).map(|(_, target)| target)
+    }
+
+    /// How many links are followed when a link points at another link, e.g. one the user made by hand.
+    /// Circular links would never reach a folder otherwise
+    const MAX_LINK_HOPS: usize = 8;
+
+    /// Whether `link` leads to `original`, directly or through a chain of other links
+    pub fn is_targeting(original: &Path, link: &Path) -> bool {
+        let mut current = link.to_path_buf();
+        for _ in 0..MAX_LINK_HOPS {
+            let Some(target) = link_target(&current) else {
+                return false;
+            };
+            let target = match current.parent() {
+                Some(parent) if target.is_relative() => parent.join(target),
+                _ => target,
+            };
+            if target == original {
+                return true;
+            }
+            current = target;
+        }
+        false
+    }
+
+    pub fn unlink_dir_if_targeting(original: &Path, link: &Path) -> std::io::Result<()> {
+        let Ok(metadata) = std::fs::symlink_metadata(link) else {
+            return Ok(());
+        };
+
+        // Links made by hand can point at another link rather than at the synced folder itself
+        #[cfg(windows)]
+        if junction::get_target(link).is_ok() {
+            if is_targeting(original, link) {
+                junction::delete(link)?;
+            }
+            return Ok(());
+        }
+
+        if metadata.is_symlink() {
+            if is_targeting(original, link) {
+                remove_symlink_dir(link)?;
+            }
+            return Ok(());
+        }
+
+        if let Some((hardlink, target)) = read_marker(link) && target == original {
+            // Anything created in the instance since the last sync would be lost otherwise
+            mirror(link, original, hardlink)?;
+            std::fs::remove_dir_all(link)?;
+        }
+
+        Ok(())
+    }
+
+    /// Brings a folder created by the Hardlink or Copy strategies up to date in both directions.
+    /// Files are added on either side and the newer version of a file wins. Deleted files come back
+    /// because there is no record of what used to be there
+    pub fn refresh_if_materialized(original: &Path, link: &Path) -> std::io::Result<()> {
+        let Some((hardlink, target)) = read_marker(link) else {
+            return Ok(());
+        };
+        if target != original {
+            return Ok(());
+        }
+
+        mirror(original, link, hardlink)?;
+        mirror(link, original, hardlink)
+    }
+
+    /// Copies everything in `from` into `to`, leaving out the marker of a Hardlink or Copy folder
+    pub fn copy_contents(from: &Path, to: &Path) -> std::io::Result<()> {
+        mirror(from, to, false)
+    }
+
+    /// The synced folder for folders created by the Hardlink or Copy strategies
+    pub fn materialized_target(link: &Path) -> Option<PathBuf> {
+        if std::fs::symlink_metadata(link).ok()?.is_symlink() {
+            return None;
+        }
+        read_marker(link).map(|(_, target)| target)
+    }
+
+    fn read_marker(link: &Path) -> Option<(bool, PathBuf)> {
+        let content = std::fs::read_to_string(link.join(MARKER_FILE)).ok()?;
+        let (kind, target) = content.split_once('\n')?;
+        Some((kind == "hardlink", PathBuf::from(target)))
+    }
+
+    fn mirror(from: &Path, to: &Path, hardlink: bool) -> std::io::Result<()> {
+        std::fs::create_dir_all(to)?;
+        for entry in walkdir::WalkDir::new(from).min_depth(1) {
+            let entry = entry?;
+            let Ok(relative) = entry.path().strip_prefix(from) else {
+                continue;
+            };
+            if relative == Path::new(MARKER_FILE) {
+                continue;
+            }
+
+            let destination = to.join(relative);
+            if entry.file_type().is_dir() {
+                std::fs::create_dir_all(&destination)?;
+                continue;
+            } else if !entry.file_type().is_file() || super::is_ignored_file(entry.path()) {
+                continue;
+            }
+            super::advance_progress();
+
+            let newer = match std::fs::metadata(&destination) {
+                Ok(existing) => entry.metadata()?.modified()? > existing.modified()?,
+                Err(_) => true,
+            };
+            if newer {
+                place_file(entry.path(), &destination, hardlink)?;
+            }
+        }
         Ok(())
     }
+
+    fn place_file(source: &Path, destination: &Path, hardlink: bool) -> std::io::Result<()> {
+        if hardlink {
+            _ = std::fs::remove_file(destination);
+            // Hardlinks can't cross filesystems, fall back to copying in that case
+            if std::fs::hard_link(source, destination).is_ok() {
+                return Ok(());
+            }
+        }
+        // Copies keep the original modification time, otherwise mirroring back would see every copy as newer
+        // and has_drifted couldn't tell a copy that was never touched apart from a modified one
+        super::copy_keeping_metadata(source, destination)
+    }
+
+    /// Whether a folder created by the Hardlink or Copy strategy has different files than the synced folder, going
+    /// by their paths, sizes and modification times
+    pub fn has_drifted(original: &Path, link: &Path) -> bool {
+        let list_files = |root: &Path| -> BTreeMap<PathBuf, (u64, Option<SystemTime>)> {
+            let mut files = BTreeMap::new();
+            for entry in walkdir::WalkDir::new(root).min_depth(1).into_iter().flatten() {
+                if !entry.file_type().is_file() || super::is_ignored_file(entry.path()) {
+                    continue;
+                }
+                let Ok(relative) = entry.path().strip_prefix(root) else {
+                    continue;
+                };
+                if relative == Path::new(MARKER_FILE) {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                files.insert(relative.to_path_buf(), (metadata.len(), metadata.modified().ok()));
+            }
+            files
+        };
+
+        list_files(original) != list_files(link)
+    }
+
+    #[cfg(unix)]
+    fn symlink_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+        std::os::unix::fs::symlink(original, link)
+    }
+
+    #[cfg(windows)]
+    fn symlink_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+        std::os::windows::fs::symlink_dir(original, link)
+    }
+
+    #[cfg(unix)]
+    fn remove_symlink_dir(link: &Path) -> std::io::Result<()> {
+        std::fs::remove_file(link)
+    }
+
+    #[cfg(windows)]
+    fn remove_symlink_dir(link: &Path) -> std::io::Result<()> {
+        std::fs::remove_dir(link)
+    }
+
+    #[cfg(windows)]
+    fn junction_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+        junction::create(original, link)
+    }
+
+    #[cfg(not(windows))]
+    fn junction_dir(original: &Path, link: &Path) -> std::io::Result<()> {
+        symlink_dir(original, link)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use rand::RngCore;
+
+        use super::*;
+
+        fn temp_dir(name: &str) -> PathBuf {
+            let path = std::env::temp_dir().join(format!("pandora_linking_{}_{}", name, rand::thread_rng().next_u64()));
+            std::fs::create_dir_all(&path).unwrap();
+            path
+        }
+
+        fn crosses_devices(_original: &Path, _link: &Path) -> std::io::Result<()> {
+            Err(std::io::Error::from(std::io::ErrorKind::CrossesDevices))
+        }
+
+        #[test]
+        fn cross_device_error_is_reported_without_fallback() {
+            let root = temp_dir("cross_device");
+            let original = root.join("store");
+            let link = root.join("instance");
+            std::fs::create_dir_all(&original).unwrap();
+
+            let result = link_or_copy(&original, &link, false, crosses_devices);
+            let exists = link.exists();
+            _ = std::fs::remove_dir_all(&root);
+
+            assert!(matches!(result, Err(LinkError::CrossesDevices(_))));
+            assert!(!exists);
+        }
+
+        #[test]
+        fn cross_device_error_copies_with_fallback() {
+            let root = temp_dir("cross_device_fallback");
+            let original = root.join("store");
+            let link = root.join("instance");
+            std::fs::create_dir_all(&original).unwrap();
+            std::fs::write(original.join("world.dat"), b"world").unwrap();
+
+            let result = link_or_copy(&original, &link, true, crosses_devices);
+            let copied = std::fs::read(link.join("world.dat")).ok();
+            let target = materialized_target(&link);
+            _ = std::fs::remove_dir_all(&root);
+
+            assert!(result.is_ok());
+            assert_eq!(copied.as_deref(), Some(&b"world"[..]));
+            assert_eq!(target, Some(original));
+        }
+
+        #[test]
+        fn link_through_another_link_is_removed() {
+            let root = temp_dir("two_hops");
+            let original = root.join("store");
+            let hop = root.join("hop");
+            let link = root.join("instance");
+            std::fs::create_dir_all(&original).unwrap();
+            junction_dir(&original, &hop).unwrap();
+            junction_dir(&hop, &link).unwrap();
+
+            let result = unlink_dir_if_targeting(&original, &link);
+            let link_exists = std::fs::symlink_metadata(&link).is_ok();
+            let hop_exists = std::fs::symlink_metadata(&hop).is_ok();
+            let original_exists = original.is_dir();
+            _ = std::fs::remove_dir_all(&root);
+
+            assert!(result.is_ok());
+            assert!(!link_exists);
+            assert!(hop_exists);
+            assert!(original_exists);
+        }
+
+        #[test]
+        fn self_referential_link_is_left_alone() {
+            let root = temp_dir("self_link");
+            let original = root.join("store");
+            let link = root.join("instance");
+            std::fs::create_dir_all(&original).unwrap();
+            junction_dir(&link, &link).unwrap();
+
+            let targeting = is_targeting(&original, &link);
+            let result = unlink_dir_if_targeting(&original, &link);
+            let link_exists = std::fs::symlink_metadata(&link).is_ok();
+            _ = std::fs::remove_dir_all(&root);
+
+            assert!(!targeting);
+            assert!(result.is_ok());
+            assert!(link_exists);
+        }
+
+        #[test]
+        fn edited_copies_are_reported_as_drifted() {
+            let root = temp_dir("drift");
+            let original = root.join("store");
+            let link = root.join("instance");
+            std::fs::create_dir_all(&original).unwrap();
+            std::fs::write(original.join("config.json"), b"{\"a\":1}").unwrap();
+            std::fs::write(original.join("notes.txt"), b"notes").unwrap();
+            materialize_dir(&original, &link, false).unwrap();
+
+            let fresh = has_drifted(&original, &link);
+            std::fs::write(link.join("config.json"), b"{\"a\":12}").unwrap();
+            let resized = has_drifted(&original, &link);
+            std::fs::copy(original.join("config.json"), link.join("config.json")).unwrap();
+            // Same size, only the modification time gives the edit away
+            let later = std::fs::metadata(original.join("config.json")).unwrap().modified().unwrap() + std::time::Duration::from_secs(60);
+            std::fs::File::options().write(true).open(link.join("config.json")).unwrap().set_modified(later).unwrap();
+            let touched = has_drifted(&original, &link);
+            _ = std::fs::remove_dir_all(&root);
+
+            assert!(!fresh);
+            assert!(resized);
+            assert!(touched);
+        }
+
+        #[test]
+        fn files_added_to_a_copy_are_reported_as_drifted() {
+            let root = temp_dir("drift_added");
+            let original = root.join("store");
+            let link = root.join("instance");
+            std::fs::create_dir_all(&original).unwrap();
+            std::fs::write(original.join("notes.txt"), b"notes").unwrap();
+            materialize_dir(&original, &link, false).unwrap();
+
+            // OS clutter doesn't count, a real file does
+            std::fs::write(link.join(".DS_Store"), b"").unwrap();
+            let clutter = has_drifted(&original, &link);
+            std::fs::create_dir_all(link.join("extra")).unwrap();
+            std::fs::write(link.join("extra/new.txt"), b"new").unwrap();
+            let added = has_drifted(&original, &link);
+            _ = std::fs::remove_dir_all(&root);
+
+            assert!(!clutter);
+            assert!(added);
+        }
+
+        #[test]
+        #[cfg(unix)]
+        fn copies_keep_unix_mode_bits() {
+            use std::os::unix::fs::PermissionsExt;
+
+            let root = temp_dir("mode_bits");
+            let original = root.join("store");
+            let link = root.join("instance");
+            std::fs::create_dir_all(original.join("scripts")).unwrap();
+            for (name, mode) in [("scripts/launch.sh", 0o755), ("private.txt", 0o600), ("shared.txt", 0o644)] {
+                std::fs::write(original.join(name), name).unwrap();
+                std::fs::set_permissions(original.join(name), std::fs::Permissions::from_mode(mode)).unwrap();
+            }
+
+            let result = mirror(&original, &link, false);
+            let modes = ["scripts/launch.sh", "private.txt", "shared.txt"]
+                .map(|name| std::fs::metadata(link.join(name)).map(|metadata| metadata.permissions().mode() & 0o777).ok());
+            _ = std::fs::remove_dir_all(&root);
+
+            assert!(result.is_ok());
+            assert_eq!(modes, [Some(0o755), Some(0o600), Some(0o644)]);
+        }
+
+        #[test]
+        #[cfg(windows)]
+        fn missing_privilege_is_typed() {
+            let error = LinkError::from_link_error(std::io::Error::from_raw_os_error(ERROR_ACCESS_DENIED));
+            assert!(matches!(error, LinkError::NeedsPrivilege(_)));
+
+            let error = LinkError::from_link_error(std::io::Error::from_raw_os_error(ERROR_PRIVILEGE_NOT_HELD));
+            assert!(matches!(error, LinkError::NeedsPrivilege(_)));
+
+            let error = std::io::Error::from(error);
+            assert_eq!(error.kind(), std::io::ErrorKind::PermissionDenied);
+            assert!(error.to_string().contains("Developer Mode"));
+        }
+
+        #[test]
+        #[cfg(windows)]
+        fn other_errors_are_not_privilege_errors() {
+            let error = LinkError::from_link_error(std::io::Error::from(std::io::ErrorKind::NotFound));
+            assert!(matches!(error, LinkError::Io(_)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silenced_progress_is_never_tracked() {
+        without_progress(|| {
+            start_progress("saves", 10);
+            assert!(CURRENT_PROGRESS.with_borrow(Option::is_none));
+        });
+
+        start_progress("saves", 10);
+        assert!(CURRENT_PROGRESS.with_borrow(Option::is_some));
+        finish_progress();
+    }
+
+    #[test]
+    fn name_synced_as_file_and_folder_is_listed_once() {
+        let (root, directories) = test_directories("sync_state");
+        let mut instances = BackendStateInstances {
+            instances: Default::default(),
+            instance_by_path: Default::default(),
+            instances_generation: 0,
+            reload_immediately: Default::default(),
+        };
+
+        let mut config = config_with_folders(&["config"]);
+        config.sync_targets.files.insert("config".into());
+        config.sync_target_filters.insert("config".into(), "loader:fabric".into());
+        reconcile(&mut config);
+        let state = get_sync_state(&config, false, &mut instances, &directories);
+        _ = std::fs::remove_dir_all(&root);
+        let state = state.unwrap();
+
+        assert_eq!(state.targets.len(), 1);
+        assert!(state.target("config", true).is_none());
+        assert!(state.target("config", false).is_some_and(|target| target.filter.as_deref() == Some("loader:fabric")));
+    }
+
+    fn test_directories(name: &str) -> (PathBuf, LauncherDirectories) {
+        let root = std::env::temp_dir().join(format!("pandora_{}_{}", name, rand::thread_rng().next_u64()));
+        let directories = LauncherDirectories::new(root.clone());
+        std::fs::create_dir_all(&directories.synced_dir).unwrap();
+        (root, directories)
+    }
+
+    /// Writes a file into the .minecraft folder of an instance, `age` minutes older than the newest one
+    fn write_instance_file(directories: &LauncherDirectories, instance: &str, relative: &str, contents: &[u8], age: u64) -> PathBuf {
+        let path = directories.instances_dir.join(instance).join(".minecraft").join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        // Creation times can't be set, so the order comes from modification times in the future
+        let modified = SystemTime::now() + Duration::from_secs(60 * 60 - age * 60);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        path
+    }
+
+    fn sources(conflict_policy: SyncConflictPolicy) -> SyncSources {
+        SyncSources {
+            sync_disabled: FxHashSet::default(),
+            conflict_policy,
+            preserved_option_keys: Vec::new(),
+        }
+    }
+
+    fn hotbar_with_item(row: usize, id: &str) -> Vec<u8> {
+        let mut hotbars = nbt::NBT::new();
+        let mut root = hotbars.as_compound_mut().unwrap();
+        root.insert_int("DataVersion", 3955);
+        for other_row in 0..HotbarNbt::ROWS {
+            let mut items = root.create_list(&other_row.to_string(), nbt::TAG_COMPOUND_ID);
+            if other_row == row {
+                let mut item = items.create_compound();
+                item.insert_string("id", id.into());
+                item.insert_byte("count", 1);
+            }
+        }
+        nbt::encode::write_named(&hotbars)
+    }
+
+    #[test]
+    fn hotbars_saved_in_different_instances_are_both_kept() {
+        let (root, directories) = test_directories("hotbar_merge");
+        write_instance_file(&directories, "a", "hotbar.nbt", &hotbar_with_item(0, "minecraft:stone"), 1);
+        write_instance_file(&directories, "b", "hotbar.nbt", &hotbar_with_item(1, "minecraft:dirt"), 0);
+
+        let combined = create_combined_hotbar_nbt(&directories.synced_dir.join("hotbar.nbt"), &sources(SyncConflictPolicy::LatestMtime), &directories);
+        _ = std::fs::remove_dir_all(&root);
+        let combined = combined.unwrap();
+
+        assert!(combined.has_items(0));
+        assert!(combined.has_items(1));
+        assert!(!combined.has_items(2));
+    }
+
+    fn write_options_operations(paths: &[&Path], store: &Path) -> Vec<SyncOperation> {
+        paths.iter()
+            .map(|path| SyncOperation::WriteOptions {
+                path: (*path).into(),
+                store: store.into(),
+                contents: "fov:1.0\n".into(),
+                changed_keys: vec!["fov".into()],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn options_are_written_to_instances_before_the_store() {
+        let (root, directories) = test_directories("options_write");
+        let first = write_instance_file(&directories, "a", "options.txt", b"fov:0.5\n", 0);
+        let second = write_instance_file(&directories, "b", "options.txt", b"fov:0.5\n", 0);
+        let store = directories.synced_dir.join("fallback_options.txt");
+
+        let operations = write_options_operations(&[first.as_path(), second.as_path()], &store);
+        let order = RefCell::new(Vec::new());
+        let report = write_options(&operations.iter().collect::<Vec<_>>(), |path, contents| {
+            order.borrow_mut().push(path.to_path_buf());
+            std::fs::write(path, contents)
+        });
+        let store_contents = std::fs::read(&store).ok();
+        _ = std::fs::remove_dir_all(&root);
+
+        assert!(report.errors.is_empty());
+        assert_eq!(report.options_changed.len(), 2);
+        assert_eq!(order.into_inner(), vec![first, second, store]);
+        assert_eq!(store_contents.as_deref(), Some(&b"fov:1.0\n"[..]));
+    }
+
+    #[test]
+    fn failed_options_write_restores_instances_and_keeps_store() {
+        let (root, directories) = test_directories("options_rollback");
+        let first = write_instance_file(&directories, "a", "options.txt", b"fov:0.5\n", 0);
+        let second = directories.instances_dir.join("b").join(".minecraft").join("options.txt");
+        let store = directories.synced_dir.join("fallback_options.txt");
+        std::fs::write(&store, b"fov:0.5\n").unwrap();
+
+        let operations = write_options_operations(&[first.as_path(), second.as_path()], &store);
+        let writes = Cell::new(0);
+        let report = write_options(&operations.iter().collect::<Vec<_>>(), |path, contents| {
+            writes.set(writes.get() + 1);
+            if writes.get() == 2 {
+                return Err(std::io::Error::other("injected failure"));
+            }
+            std::fs::write(path, contents)
+        });
+        let first_contents = std::fs::read(&first).ok();
+        let second_exists = second.exists();
+        let store_contents = std::fs::read(&store).ok();
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.options_changed.is_empty());
+        assert_eq!(first_contents.as_deref(), Some(&b"fov:0.5\n"[..]));
+        assert!(!second_exists);
+        assert_eq!(store_contents.as_deref(), Some(&b"fov:0.5\n"[..]));
+    }
+
+    #[test]
+    fn filters_can_pick_out_a_sync_group() {
+        let mut grouped = InstanceConfiguration::new("1.21.1".into(), schema::loader::Loader::Fabric);
+        grouped.sync_group = Some("modded".into());
+        let ungrouped = InstanceConfiguration::new("1.21.1".into(), schema::loader::Loader::Fabric);
+
+        assert!(instance_matches_filter("group:modded", "a", &grouped));
+        assert!(!instance_matches_filter("group:modded", "b", &ungrouped));
+        assert!(instance_matches_filter("!group:modded", "b", &ungrouped));
+        assert!(instance_matches_filter("group:", "b", &ungrouped));
+    }
+
+    #[test]
+    fn merged_options_are_stable_and_ordered_like_the_newest_file() {
+        let (root, directories) = test_directories("options_order");
+        write_instance_file(&directories, "a", "options.txt", b"fov:0.5\nguiScale:2\nlang:en_us\n", 1);
+        let current = write_instance_file(&directories, "b", "options.txt", b"lang:de_de\nfov:0.7\n", 0);
+        let fallback = directories.synced_dir.join("fallback_options.txt");
+        let sources = sources(SyncConflictPolicy::LatestMtime);
+
+        let first = create_combined_options_txt(&fallback, &current, &sources, &directories);
+        let second = create_combined_options_txt(&fallback, &current, &sources, &directories);
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(first.as_deref(), Some("lang:de_de\nfov:0.7\nguiScale:2\n"));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn disabled_instance_is_never_the_source() {
+        let (root, directories) = test_directories("disabled_source");
+        let enabled = write_instance_file(&directories, "a", "servers.json", b"old", 1);
+        write_instance_file(&directories, "b", "servers.json", b"newer and larger", 0);
+        let file = SafePath::new("servers.json").unwrap();
+
+        let mut chosen = Vec::new();
+        for conflict_policy in [SyncConflictPolicy::LatestMtime, SyncConflictPolicy::LargestFile, SyncConflictPolicy::MajorityHash] {
+            let mut sources = sources(conflict_policy);
+            sources.sync_disabled.insert(directories.instances_dir.join("b"));
+            chosen.push(find_latest(&file, &sources, &directories));
+        }
+        _ = std::fs::remove_dir_all(&root);
+
+        for source in chosen {
+            assert_eq!(source.as_ref(), Some(&enabled));
+        }
+    }
+
+    #[test]
+    fn options_with_crlf_and_colons_in_values_are_read() {
+        let (root, directories) = test_directories("options_crlf");
+        let path = write_instance_file(&directories, "a", "options.txt", b"fov:0.5\r\nlastServer:play.example.com:25565\r\n", 0);
+
+        let values = try_read_options_txt(&path);
+        _ = std::fs::remove_dir_all(&root);
+        let values = values.unwrap().unwrap();
+
+        assert_eq!(values.len(), 2);
+        assert_eq!(values.get("fov").map(String::as_str), Some("0.5"));
+        assert_eq!(values.get("lastServer").map(String::as_str), Some("play.example.com:25565"));
+    }
+
+    #[test]
+    fn options_comments_blank_lines_and_empty_values_are_handled() {
+        let (root, directories) = test_directories("options_comments");
+        let path = write_instance_file(&directories, "a", "options.txt", b"# written by a mod: do not edit\n\nfov:0.5\n   \nsoundDevice:\n", 0);
+
+        let values = try_read_options_txt(&path);
+        _ = std::fs::remove_dir_all(&root);
+        let values = values.unwrap().unwrap();
+
+        assert_eq!(values.keys().map(String::as_str).collect::<Vec<_>>(), ["fov", "soundDevice"]);
+        assert_eq!(values.get("soundDevice").map(String::as_str), Some(""));
+    }
+
+    #[test]
+    fn majority_outvotes_a_newer_copy() {
+        let (root, directories) = test_directories("majority_hash");
+        write_instance_file(&directories, "a", "servers.json", b"agreed", 2);
+        let newest_agreeing = write_instance_file(&directories, "b", "servers.json", b"agreed", 1);
+        write_instance_file(&directories, "c", "servers.json", b"different", 0);
+
+        let file = SafePath::new("servers.json").unwrap();
+        let chosen = find_latest(&file, &sources(SyncConflictPolicy::MajorityHash), &directories);
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(chosen, Some(newest_agreeing));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn links_into_deleted_synced_folders_are_cleaned_up() {
+        let (root, directories) = test_directories("dead_links");
+        let dot_minecraft = directories.instances_dir.join("a").join(".minecraft");
+        std::fs::create_dir_all(&dot_minecraft).unwrap();
+        // Neither synced folder exists, screenshots is no longer a target while saves still is
+        for name in ["saves", "screenshots"] {
+            std::os::unix::fs::symlink(directories.synced_dir.join(name), dot_minecraft.join(name)).unwrap();
+        }
+        let sync_targets = config_with_folders(&["saves"]).sync_targets;
+
+        let plan = plan_apply_to_instance(&sync_targets, SyncDirection::Auto, &sources(SyncConflictPolicy::LatestMtime), &directories, &dot_minecraft);
+        let report = execute_apply_plan(&plan, LinkStrategy::Symlink, false);
+        let screenshots_left = std::fs::symlink_metadata(dot_minecraft.join("screenshots")).is_ok();
+        let saves_repaired = dot_minecraft.join("saves").is_dir();
+        _ = std::fs::remove_dir_all(&root);
+
+        assert!(report.errors.is_empty());
+        assert_eq!(report.links_removed, [Arc::<str>::from("screenshots")]);
+        assert_eq!(report.links_repaired, [Arc::<str>::from("saves")]);
+        assert!(!screenshots_left);
+        assert!(saves_repaired);
+    }
+
+    #[test]
+    fn plan_runs_removals_before_creating_links_at_the_same_paths() {
+        let (root, directories) = test_directories("parallel_plan");
+        let nested = ["config/a", "config/b", "config/c/d", "config/c/e"];
+
+        let mut results = Vec::new();
+        for run in 0..8 {
+            let dot_minecraft = directories.instances_dir.join(format!("run{run}")).join(".minecraft");
+            std::fs::create_dir_all(&dot_minecraft).unwrap();
+            let old_saves = directories.synced_dir.join("old_saves");
+            std::fs::create_dir_all(&old_saves).unwrap();
+            linking::link_dir(&old_saves, &dot_minecraft.join("saves"), LinkStrategy::Junction, false).unwrap();
+
+            let link = |name: &str| SyncOperation::CreateLink {
+                name: name.into(),
+                path: dot_minecraft.join(name).into(),
+                target: directories.synced_dir.join(name).into(),
+            };
+            // The creates come first to show that the order within the plan doesn't matter
+            let mut operations: Vec<SyncOperation> = nested.into_iter().map(&link).collect();
+            operations.push(link("saves"));
+            operations.push(SyncOperation::RemoveLink {
+                name: "saves".into(),
+                path: dot_minecraft.join("saves").into(),
+                target: old_saves.into(),
+            });
+
+            let report = execute_apply_plan(&ApplyPlan { operations, ..Default::default() }, LinkStrategy::Junction, false);
+            let linked = nested.iter().chain(["saves"].iter())
+                .all(|name| linking::is_targeting(&directories.synced_dir.join(name), &dot_minecraft.join(name)));
+            results.push((report.errors.len(), report.links_created.len(), linked));
+        }
+        _ = std::fs::remove_dir_all(&root);
+
+        for (errors, created, linked) in results {
+            assert_eq!(errors, 0);
+            assert_eq!(created, nested.len() + 1);
+            assert!(linked);
+        }
+    }
+
+    #[test]
+    fn targets_behind_a_link_out_of_dot_minecraft_are_skipped() {
+        let (root, directories) = test_directories("linked_parent");
+        let dot_minecraft = directories.instances_dir.join("a").join(".minecraft");
+        let shared = root.join("shared");
+        std::fs::create_dir_all(&shared).unwrap();
+        std::fs::create_dir_all(dot_minecraft.join("real_settings")).unwrap();
+        // Junctions on Windows and symlinks elsewhere, one leaving .minecraft and one staying inside it
+        linking::link_dir(&shared, &dot_minecraft.join("config"), LinkStrategy::Junction, false).unwrap();
+        linking::link_dir(&dot_minecraft.join("real_settings"), &dot_minecraft.join("settings"), LinkStrategy::Junction, false).unwrap();
+        std::fs::create_dir_all(directories.synced_dir.join("config")).unwrap();
+        std::fs::write(directories.synced_dir.join("config").join("evil.json"), b"{}").unwrap();
+
+        let sync_targets = SyncTargets {
+            files: ["config/evil.json"].into_iter().map(Arc::from).collect(),
+            folders: ["config/mods", "settings/mods"].into_iter().map(Arc::from).collect(),
+        };
+        let plan = plan_apply_to_instance(&sync_targets, SyncDirection::Push, &sources(SyncConflictPolicy::LatestMtime), &directories, &dot_minecraft);
+        let names: Vec<Arc<str>> = plan.operations.iter()
+            .filter_map(|operation| match operation {
+                SyncOperation::CreateLink { name, .. } | SyncOperation::CopyFile { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        let report = execute_apply_plan(&plan, LinkStrategy::Junction, false);
+        let shared_entries = std::fs::read_dir(&shared).map(|entries| entries.count()).unwrap_or(0);
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(names, [Arc::<str>::from("settings/mods")]);
+        assert!(report.errors.is_empty());
+        assert_eq!(shared_entries, 0);
+    }
+
+    fn created_links(plan: &ApplyPlan) -> Vec<Arc<str>> {
+        plan.operations.iter()
+            .filter_map(|operation| match operation {
+                SyncOperation::CreateLink { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nested_folder_targets_are_linked_once() {
+        let (root, directories) = test_directories("nested_targets");
+        let sources = sources(SyncConflictPolicy::LatestMtime);
+        let nested_only = config_with_folders(&["config/fabric"]).sync_targets;
+        let with_parent = config_with_folders(&["config", "config/fabric"]).sync_targets;
+
+        let mut results = Vec::new();
+        for (instance, sync_targets) in [("a", &nested_only), ("b", &with_parent)] {
+            let dot_minecraft = directories.instances_dir.join(instance).join(".minecraft");
+            std::fs::create_dir_all(&dot_minecraft).unwrap();
+
+            let plan = plan_apply_to_instance(sync_targets, SyncDirection::Auto, &sources, &directories, &dot_minecraft);
+            let created = created_links(&plan);
+            let report = execute_apply_plan(&plan, LinkStrategy::Junction, false);
+            let replanned = plan_apply_to_instance(sync_targets, SyncDirection::Auto, &sources, &directories, &dot_minecraft);
+            results.push((created, report.errors.is_empty(), replanned.is_empty(), dot_minecraft));
+        }
+
+        let nested_linked = linking::is_targeting(&directories.synced_dir.join("config/fabric"), &results[0].3.join("config/fabric"));
+        let parent_is_real = linking::link_target(&results[0].3.join("config")).is_none();
+        let parent_linked = linking::is_targeting(&directories.synced_dir.join("config"), &results[1].3.join("config"));
+        let store_has_link = linking::link_target(&directories.synced_dir.join("config/fabric")).is_some();
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(results[0].0, [Arc::<str>::from("config/fabric")]);
+        assert_eq!(results[1].0, [Arc::<str>::from("config")]);
+        assert!(results.iter().all(|(_, no_errors, replanned_empty, _)| *no_errors && *replanned_empty));
+        assert!(nested_linked);
+        assert!(parent_is_real);
+        assert!(parent_linked);
+        assert!(!store_has_link);
+    }
+
+    #[test]
+    fn unreadable_current_options_are_left_alone() {
+        let (root, directories) = test_directories("unreadable_options");
+        // Reading a folder fails on every platform, even when running as root
+        let unreadable = directories.instances_dir.join("a").join(".minecraft").join("options.txt");
+        std::fs::create_dir_all(&unreadable).unwrap();
+        let readable = write_instance_file(&directories, "b", "options.txt", b"fov:0.5\nresourcePacks:[\"vanilla\"]\n", 0);
+        let fallback = directories.synced_dir.join("fallback_options.txt");
+        let sources = sources(SyncConflictPolicy::LatestMtime);
+        let sync_targets = SyncTargets {
+            files: ["options.txt"].into_iter().map(Arc::from).collect(),
+            folders: BTreeSet::new(),
+        };
+
+        let combined_unreadable = create_combined_options_txt(&fallback, &unreadable, &sources, &directories);
+        let combined_readable = create_combined_options_txt(&fallback, &readable, &sources, &directories);
+        let plan = plan_apply_to_instance(&sync_targets, SyncDirection::Auto, &sources, &directories, unreadable.parent().unwrap());
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(combined_unreadable, None);
+        assert_eq!(combined_readable.as_deref(), Some("fov:0.5\nresourcePacks:[\"vanilla\"]\n"));
+        assert!(!plan.operations.iter().any(|operation| matches!(operation, SyncOperation::WriteOptions { .. })));
+    }
+
+    #[test]
+    fn disabling_nested_folders_removes_only_empty_synced_parents() {
+        let (root, directories) = test_directories("disable_nested");
+        let dot_minecraft = directories.instances_dir.join("a").join(".minecraft");
+        std::fs::create_dir_all(dot_minecraft.join("config")).unwrap();
+        // config/jei still has a file in the store after being disabled, config/fabric is empty
+        std::fs::create_dir_all(directories.synced_dir.join("config/fabric")).unwrap();
+        std::fs::create_dir_all(directories.synced_dir.join("config/jei")).unwrap();
+        std::fs::write(directories.synced_dir.join("config/jei/jei.json"), b"{}").unwrap();
+        for name in ["config/fabric", "config/jei"] {
+            linking::link_dir(&directories.synced_dir.join(name), &dot_minecraft.join(name), LinkStrategy::Junction, false).unwrap();
+        }
+
+        let mut checkpoint = SyncCheckpoint::begin("config/fabric".into(), false, false, false, &directories);
+        disable_all("config/fabric", false, &mut checkpoint, &directories).unwrap();
+        checkpoint.finish();
+        let fabric_removed = !directories.synced_dir.join("config/fabric").exists();
+        let config_kept = directories.synced_dir.join("config").is_dir();
+
+        let mut checkpoint = SyncCheckpoint::begin("config/jei".into(), false, false, false, &directories);
+        disable_all("config/jei", false, &mut checkpoint, &directories).unwrap();
+        checkpoint.finish();
+        let jei_kept = directories.synced_dir.join("config/jei/jei.json").is_file();
+        let links_removed = ["config/fabric", "config/jei"].iter()
+            .all(|name| std::fs::symlink_metadata(dot_minecraft.join(name)).is_err());
+        _ = std::fs::remove_dir_all(&root);
+
+        assert!(fabric_removed);
+        assert!(config_kept);
+        assert!(jei_kept);
+        assert!(links_removed);
+    }
+
+    #[test]
+    fn disabling_the_last_nested_folder_removes_its_empty_parents() {
+        let (root, directories) = test_directories("disable_nested_parents");
+        let dot_minecraft = directories.instances_dir.join("a").join(".minecraft");
+        std::fs::create_dir_all(directories.synced_dir.join("config/fabric/extra")).unwrap();
+        std::fs::create_dir_all(dot_minecraft.join("config/fabric")).unwrap();
+        linking::link_dir(&directories.synced_dir.join("config/fabric/extra"), &dot_minecraft.join("config/fabric/extra"), LinkStrategy::Junction, false).unwrap();
+
+        let mut checkpoint = SyncCheckpoint::begin("config/fabric/extra".into(), false, false, false, &directories);
+        disable_all("config/fabric/extra", false, &mut checkpoint, &directories).unwrap();
+        checkpoint.finish();
+        let store_emptied = !directories.synced_dir.join("config").exists();
+        let synced_dir_kept = directories.synced_dir.is_dir();
+        _ = std::fs::remove_dir_all(&root);
+
+        assert!(store_emptied);
+        assert!(synced_dir_kept);
+    }
+
+    #[test]
+    fn file_patterns_match_and_sync_only_their_files() {
+        let (root, directories) = test_directories("file_patterns");
+        write_instance_file(&directories, "a", "config/sodium.json", b"{\"sodium\":true}", 1);
+        write_instance_file(&directories, "a", "config/iris.json", b"{\"iris\":true}", 0);
+        write_instance_file(&directories, "a", "config/notes.txt", b"notes", 0);
+        write_instance_file(&directories, "a", "config/nested/deep.json", b"{}", 0);
+        let dot_minecraft = directories.instances_dir.join("b").join(".minecraft");
+        std::fs::create_dir_all(&dot_minecraft).unwrap();
+        let sync_targets = SyncTargets {
+            files: ["config/*.json"].into_iter().map(Arc::from).collect(),
+            folders: BTreeSet::new(),
+        };
+
+        let expanded = expand_file_pattern("config/*.json", &[directories.instances_dir.join("a").join(".minecraft")]);
+        let plan = plan_apply_to_instance(&sync_targets, SyncDirection::Auto, &sources(SyncConflictPolicy::LatestMtime), &directories, &dot_minecraft);
+        let report = execute_apply_plan(&plan, LinkStrategy::Junction, false);
+        let copied = [std::fs::read(dot_minecraft.join("config/sodium.json")).ok(), std::fs::read(dot_minecraft.join("config/iris.json")).ok()];
+        let stored = [directories.synced_dir.join("config/sodium.json").is_file(), directories.synced_dir.join("config/iris.json").is_file()];
+        let unmatched_copied = dot_minecraft.join("config/notes.txt").exists() || dot_minecraft.join("config/nested").exists();
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(expanded, ["config/iris.json", "config/sodium.json"].into_iter().map(Arc::from).collect::<BTreeSet<Arc<str>>>());
+        assert!(is_file_target("config/sodium.json", &sync_targets));
+        assert!(!is_file_target("config/notes.txt", &sync_targets));
+        assert!(!is_file_target("config/nested/deep.json", &sync_targets));
+        assert!(report.errors.is_empty());
+        assert_eq!(copied, [Some(b"{\"sodium\":true}".to_vec()), Some(b"{\"iris\":true}".to_vec())]);
+        assert_eq!(stored, [true, true]);
+        assert!(!unmatched_copied);
+    }
+
+    #[test]
+    fn case_variants_are_found_in_mixed_case_targets() {
+        let mut config = config_with_folders(&["config", "Saves", "resourcepacks"]);
+        config.sync_targets.files.insert("Options.txt".into());
+
+        assert_eq!(find_case_variant("Config", &config.sync_targets).as_deref(), Some("config"));
+        assert_eq!(find_case_variant("saves", &config.sync_targets).as_deref(), Some("Saves"));
+        assert_eq!(find_case_variant("options.TXT", &config.sync_targets).as_deref(), Some("Options.txt"));
+        assert_eq!(find_case_variant("config", &config.sync_targets), None);
+        assert_eq!(find_case_variant("mods", &config.sync_targets), None);
+
+        // Variants only merge where the filesystem would put them in the same folder
+        config.sync_targets.folders.insert("Config".into());
+        let merged = reconcile(&mut config);
+        let config_folders = config.sync_targets.folders.iter().filter(|folder| folder.eq_ignore_ascii_case("config")).count();
+        if cfg!(any(windows, target_os = "macos")) {
+            assert_eq!(merged.len(), 1);
+            assert_eq!(config_folders, 1);
+        } else {
+            assert!(merged.is_empty());
+            assert_eq!(config_folders, 2);
+        }
+    }
+
+    #[test]
+    fn case_insensitivity_is_detected_from_the_synced_folder() {
+        let (root, directories) = test_directories("case_probe");
+        std::fs::write(directories.synced_dir.join("probe"), b"").unwrap();
+        let expected = directories.synced_dir.join("PROBE").exists();
+        let detected = is_case_insensitive(&directories);
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(detected, expected);
+    }
+
+    #[test]
+    fn custom_preserved_keys_survive_the_merge() {
+        let (root, directories) = test_directories("custom_preserved");
+        write_instance_file(&directories, "a", "options.txt", b"key_key.jump:key.keyboard.j\nfov:0.5\nresourcePacks:[\"a\"]\n", 0);
+        let current = write_instance_file(&directories, "b", "options.txt", b"fov:0.7\nkey_key.jump:key.keyboard.space\nresourcePacks:[\"b\"]\n", 1);
+        let fallback = directories.synced_dir.join("fallback_options.txt");
+        let mut sources = sources(SyncConflictPolicy::LatestMtime);
+        sources.preserved_option_keys = vec!["key_key.*".into()];
+
+        let combined = create_combined_options_txt(&fallback, &current, &sources, &directories);
+        _ = std::fs::remove_dir_all(&root);
+
+        // Custom keys replace the defaults, so the resource packs are synced like any other option
+        assert_eq!(combined.as_deref(), Some("fov:0.5\nresourcePacks:[\"a\"]\nkey_key.jump:key.keyboard.space\n"));
+    }
+
+    #[test]
+    fn default_and_user_ignore_patterns_are_applied() {
+        for name in [".DS_Store", "._screenshot.png", "Thumbs.db", "desktop.ini"] {
+            assert!(is_ignored_file(&Path::new("saves").join(name)), "{name} should be ignored");
+        }
+        for name in ["options.txt", "DS_Store", "thumbs.db.png"] {
+            assert!(!is_ignored_file(&Path::new("saves").join(name)), "{name} shouldn't be ignored");
+        }
+
+        // The patterns are global, so this uses names that no other test creates
+        set_ignore_patterns(&["*.pandora_ignored".into(), "pandora_scratch_?".into()]);
+        let user_ignored = [is_ignored_file(Path::new("config/a.pandora_ignored")), is_ignored_file(Path::new("pandora_scratch_1"))];
+        let user_kept = [is_ignored_file(Path::new("pandora_scratch_10")), is_ignored_file(Path::new("config/a.pandora_ignored.json"))];
+        set_ignore_patterns(&[]);
+        let cleared = is_ignored_file(Path::new("config/a.pandora_ignored"));
+
+        assert_eq!(user_ignored, [true, true]);
+        assert_eq!(user_kept, [false, false]);
+        assert!(!cleared);
+        assert!(is_ignored_file(Path::new(".DS_Store")));
+    }
+
+    /// Loads real instances the same way the sync simulation does, for the functions that walk BackendStateInstances
+    fn load_instances(directories: &LauncherDirectories, names: &[&str], configure: impl Fn(&str, &mut InstanceConfiguration)) -> BackendStateInstances {
+        let mut instances = BackendStateInstances {
+            instances: Default::default(),
+            instance_by_path: Default::default(),
+            instances_generation: 0,
+            reload_immediately: Default::default(),
+        };
+        for name in names {
+            let instance_dir = directories.instances_dir.join(name);
+            std::fs::create_dir_all(instance_dir.join(".minecraft")).unwrap();
+            let mut configuration = InstanceConfiguration::new("1.21.1".into(), schema::loader::Loader::Vanilla);
+            configure(name, &mut configuration);
+            std::fs::write(instance_dir.join("info_v1.json"), serde_json::to_vec(&configuration).unwrap()).unwrap();
+
+            let mut instance = crate::instance::Instance::load_from_folder(&instance_dir).unwrap();
+            instances.instances.insert(move |index| {
+                instance.id = bridge::instance::InstanceID { index, generation: 0 };
+                instance
+            });
+        }
+        instances
+    }
+
+    fn enable_folder(name: &str, instances: &mut BackendStateInstances, directories: &LauncherDirectories) -> bool {
+        let mut checkpoint = SyncCheckpoint::begin(name.into(), false, true, false, directories);
+        let enabled = enable_all(name, false, None, LinkStrategy::Junction, false, &mut checkpoint, instances, directories).unwrap();
+        checkpoint.finish();
+        enabled
+    }
+
+    #[test]
+    fn renamed_targets_move_in_the_store_and_relink_instances() {
+        let (root, directories) = test_directories("rename_targets");
+        let mut instances = load_instances(&directories, &["a", "b"], |_, _| {});
+        std::fs::create_dir_all(directories.synced_dir.join("shaderpacks")).unwrap();
+        std::fs::write(directories.synced_dir.join("shaderpacks/pack.zip"), b"pack").unwrap();
+        std::fs::write(directories.synced_dir.join("notes.txt"), b"notes").unwrap();
+        let enabled = enable_folder("shaderpacks", &mut instances, &directories);
+
+        let folder_renamed = rename_target("shaderpacks", "shaders", false, LinkStrategy::Junction, false, &mut instances, &directories);
+        let file_renamed = rename_target("notes.txt", "docs/notes.txt", true, LinkStrategy::Junction, false, &mut instances, &directories);
+        let relinked = ["a", "b"].map(|name| {
+            let dot_minecraft = directories.instances_dir.join(name).join(".minecraft");
+            linking::is_targeting(&directories.synced_dir.join("shaders"), &dot_minecraft.join("shaders"))
+                && std::fs::symlink_metadata(dot_minecraft.join("shaderpacks")).is_err()
+        });
+        let pack = std::fs::read(directories.instances_dir.join("a").join(".minecraft").join("shaders/pack.zip")).ok();
+        let old_folder_left = directories.synced_dir.join("shaderpacks").exists();
+        let notes = std::fs::read(directories.synced_dir.join("docs/notes.txt")).ok();
+        let old_file_left = directories.synced_dir.join("notes.txt").exists();
+        // Renaming onto a name the store already has would overwrite it
+        std::fs::write(directories.synced_dir.join("taken.txt"), b"").unwrap();
+        let onto_existing = rename_target("docs/notes.txt", "taken.txt", true, LinkStrategy::Junction, false, &mut instances, &directories);
+        _ = std::fs::remove_dir_all(&root);
+
+        assert!(enabled);
+        assert!(folder_renamed.is_ok());
+        assert!(file_renamed.is_ok());
+        assert_eq!(relinked, [true, true]);
+        assert_eq!(pack.as_deref(), Some(&b"pack"[..]));
+        assert!(!old_folder_left);
+        assert_eq!(notes.as_deref(), Some(&b"notes"[..]));
+        assert!(!old_file_left);
+        assert_eq!(onto_existing.map_err(|error| error.kind()), Err(std::io::ErrorKind::AlreadyExists));
+    }
+
+    #[test]
+    fn concurrent_enable_and_disable_behind_the_sync_lock() {
+        let (root, directories) = test_directories("toggle_stress");
+        let instances = RwLock::new(load_instances(&directories, &["a", "b", "c"], |_, _| {}));
+        std::fs::create_dir_all(directories.synced_dir.join("shaderpacks")).unwrap();
+        std::fs::write(directories.synced_dir.join("shaderpacks/pack.zip"), b"pack").unwrap();
+        let sync_lock = parking_lot::Mutex::new(());
+
+        // Every thread toggles the shared target as well as its own, the way try_set_syncing takes the lock for each change
+        let failures: Vec<String> = std::thread::scope(|scope| {
+            let threads: Vec<_> = (0..4).map(|thread| {
+                let (directories, instances, sync_lock) = (&directories, &instances, &sync_lock);
+                scope.spawn(move || {
+                    let own = format!("config/thread{}", thread);
+                    let mut failures = Vec::new();
+                    for _ in 0..10 {
+                        for target in ["shaderpacks", own.as_str()] {
+                            for value in [true, false] {
+                                let _sync_lock = sync_lock.lock();
+                                let mut checkpoint = SyncCheckpoint::begin(target.into(), false, value, false, directories);
+                                let result = if value {
+                                    enable_all(target, false, None, LinkStrategy::Junction, false, &mut checkpoint, &mut *instances.write(), directories)
+                                } else {
+                                    disable_all(target, false, &mut checkpoint, directories).map(|_| true)
+                                };
+                                checkpoint.finish();
+                                if !matches!(result, Ok(true)) {
+                                    failures.push(format!("{} {}: {:?}", if value { "enabling" } else { "disabling" }, target, result));
+                                }
+                            }
+                        }
+                    }
+                    failures
+                })
+            }).collect();
+            threads.into_iter().flat_map(|thread| thread.join().unwrap()).collect()
+        });
+
+        let links_left = ["a", "b", "c"].iter().any(|name| {
+            std::fs::symlink_metadata(directories.instances_dir.join(name).join(".minecraft").join("shaderpacks")).is_ok()
+        });
+        let pack = std::fs::read(directories.synced_dir.join("shaderpacks/pack.zip")).ok();
+        let checkpoint_left = directories.synced_dir.join(CHECKPOINT_FILE).exists();
+        let empty_folders_left = directories.synced_dir.join("config").exists();
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(failures, Vec::<String>::new());
+        assert!(!links_left);
+        assert_eq!(pack.as_deref(), Some(&b"pack"[..]));
+        assert!(!checkpoint_left);
+        assert!(!empty_folders_left);
+    }
+
+    #[test]
+    fn nested_or_overlapping_synced_folders_are_refused() {
+        let (root, mut directories) = test_directories("synced_dir_location");
+        let instances = load_instances(&directories, &["a"], |_, _| {});
+        let dot_minecraft = directories.instances_dir.join("a").join(".minecraft");
+        std::fs::create_dir_all(dot_minecraft.join("shared")).unwrap();
+        linking::link_dir(&dot_minecraft.join("shared"), &root.join("linked_synced"), LinkStrategy::Junction, false).unwrap();
+
+        let mut check = |synced_dir: PathBuf| {
+            std::fs::create_dir_all(&synced_dir).unwrap();
+            directories.synced_dir = synced_dir.into();
+            check_synced_dir_location(&directories, &instances).is_ok()
+        };
+        let results = [
+            check(root.join("synced")),
+            // Only whole components count, a sibling that shares a prefix doesn't overlap
+            check(root.join("instances_synced")),
+            check(root.join("instances").join("synced")),
+            check(dot_minecraft.join("synced")),
+            check(root.join("linked_synced")),
+        ];
+        let instances_inside = {
+            directories.synced_dir = root.clone().into();
+            check_synced_dir_location(&directories, &instances).is_ok()
+        };
+        _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(results, [true, true, false, false, false]);
+        assert!(!instances_inside);
+    }
+
+    #[test]
+    fn dot_minecraft_linked_into_the_synced_folder_is_refused() {
+        let (root, directories) = test_directories("dot_minecraft_location");
+        let instances = load_instances(&directories, &["a"], |_, _| {});
+        let dot_minecraft = directories.instances_dir.join("a").join(".minecraft");
+        let before = check_synced_dir_location(&directories, &instances).is_ok();
+
+        std::fs::remove_dir(&dot_minecraft).unwrap();
+        std::fs::create_dir_all(directories.synced_dir.join("a")).unwrap();
+        linking::link_dir(&directories.synced_dir.join("a"), &dot_minecraft, LinkStrategy::Junction, false).unwrap();
+        let after = check_synced_dir_location(&directories, &instances).is_ok();
+        _ = std::fs::remove_dir_all(&root);
+
+        assert!(before);
+        assert!(!after);
+    }
+
+    #[test]
+    fn excluded_instances_are_neither_linked_nor_counted() {
+        let (root, directories) = test_directories("exclusions");
+        let mut instances = load_instances(&directories, &["a", "b"], |name, configuration| {
+            if name == "b" {
+                configuration.sync_exclusions.insert("shaderpacks".into());
+                configuration.sync_exclusions.insert("options.txt".into());
+            }
+        });
+        // The excluded instance keeps its own folder, which would otherwise count as unable to sync
+        let excluded_folder = directories.instances_dir.join("b").join(".minecraft").join("shaderpacks");
+        std::fs::create_dir_all(&excluded_folder).unwrap();
+        let mut config = config_with_folders(&["shaderpacks"]);
+        config.sync_targets.files.insert("options.txt".into());
+
+        let enabled = enable_folder("shaderpacks", &mut instances, &directories);
+        let included_linked = linking::is_targeting(&directories.synced_dir.join("shaderpacks"),
+            &directories.instances_dir.join("a").join(".minecraft").join("shaderpacks"));
+        let excluded_untouched = excluded_folder.is_dir() && linking::link_target(&excluded_folder).is_none();
+        let state = get_sync_state(&config, false, &mut instances, &directories);
+        _ = std::fs::remove_dir_all(&root);
+        let state = state.unwrap();
+
+        assert!(enabled);
+        assert!(included_linked);
+        assert!(excluded_untouched);
+        let folder = state.target("shaderpacks", false).unwrap();
+        assert_eq!((folder.sync_count, folder.cannot_sync_count, folder.matching_count, folder.excluded_count), (1, 0, 1, 1));
+        let file = state.target("options.txt", true).unwrap();
+        assert_eq!((file.sync_count, file.matching_count, file.excluded_count), (1, 1, 1));
+    }
+
+    #[test]
+    fn disabling_several_targets_lists_the_instances_once() {
+        let targets = ["saves", "shaderpacks"];
+        let link_all = |directories: &LauncherDirectories, instance: &str| {
+            let dot_minecraft = directories.instances_dir.join(instance).join(".minecraft");
+            std::fs::create_dir_all(&dot_minecraft).unwrap();
+            for target in targets {
+                std::fs::create_dir_all(directories.synced_dir.join(target)).unwrap();
+                linking::link_dir(&directories.synced_dir.join(target), &dot_minecraft.join(target), LinkStrategy::Junction, false).unwrap();
+            }
+        };
+        let unlinked = |directories: &LauncherDirectories| -> BTreeSet<PathBuf> {
+            ["a", "b", "c"].iter()
+                .flat_map(|instance| targets.map(|target| PathBuf::from(instance).join(".minecraft").join(target)))
+                .filter(|relative| std::fs::symlink_metadata(directories.instances_dir.join(relative)).is_err())
+                .collect()
+        };
+        let disable = |directories: &LauncherDirectories, run: &dyn Fn(&str, &mut SyncCheckpoint)| {
+            for target in targets {
+                let mut checkpoint = SyncCheckpoint::begin(target.into(), false, false, false, directories);
+                run(target, &mut checkpoint);
+                checkpoint.finish();
+            }
+        };
+
+        // "c" only appears after the instances were listed, so it's left alone when the list is reused
+        let (listed_root, listed) = test_directories("disable_listed");
+        link_all(&listed, "a");
+        link_all(&listed, "b");
+        let dot_minecraft_dirs = list_dot_minecraft_dirs(&listed).unwrap();
+        link_all(&listed, "c");
+        disable(&listed, &|target, checkpoint| disable_all_in(target, false, &dot_minecraft_dirs, checkpoint, &listed).unwrap());
+        let listed_unlinked = unlinked(&listed);
+        _ = std::fs::remove_dir_all(&listed_root);
+
+        let (separate_root, separate) = test_directories("disable_separate");
+        link_all(&separate, "a");
+        link_all(&separate, "b");
+        link_all(&separate, "c");
+        disable(&separate, &|target, checkpoint| disable_all(target, false, checkpoint, &separate).unwrap());
+        let separate_unlinked = unlinked(&separate);
+        _ = std::fs::remove_dir_all(&separate_root);
+
+        let mut expected: Vec<PathBuf> = dot_minecraft_dirs.iter().map(|path| path.strip_prefix(&*listed.instances_dir).unwrap().to_path_buf()).collect();
+        expected.sort();
+        assert_eq!(expected, [PathBuf::from("a").join(".minecraft"), PathBuf::from("b").join(".minecraft")]);
+        assert_eq!(listed_unlinked, separate_unlinked.iter().filter(|path| !path.starts_with("c")).cloned().collect::<BTreeSet<PathBuf>>());
+        assert_eq!(listed_unlinked.len(), 4);
+        assert_eq!(separate_unlinked.len(), 6);
+    }
+
+    fn config_with_folders(folders: &[&str]) -> BackendConfig {
+        BackendConfig {
+            sync_targets: SyncTargets {
+                files: BTreeSet::new(),
+                folders: folders.iter().map(|folder| Arc::from(*folder)).collect(),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn reconcile(config: &mut BackendConfig) -> Vec<MergedSyncTarget> {
+        let merged = reconcile_sync_targets(&mut config.sync_targets);
+        for merged in &merged {
+            move_target_settings(config, &merged.from, &merged.into);
+        }
+        merged
+    }
+
+    #[test]
+    fn merged_target_keeps_filter_and_verified_state() {
+        let mut config = config_with_folders(&["config/", "saves"]);
+        config.sync_target_filters.insert("config/".into(), "loader:fabric".into());
+        config.verified_sync_targets.insert("config/".into());
+
+        let merged = reconcile(&mut config);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(&*merged[0].into, "config");
+        assert_eq!(config.sync_targets.folders, ["config", "saves"].into_iter().map(Arc::from).collect::<BTreeSet<_>>());
+        assert_eq!(config.sync_target_filters.get("config").map(|filter| &**filter), Some("loader:fabric"));
+        assert!(!config.sync_target_filters.contains_key("config/"));
+        assert!(config.verified_sync_targets.contains("config"));
+        assert!(!config.verified_sync_targets.contains("config/"));
+    }
+
+    #[test]
+    fn merged_target_keeps_its_own_filter() {
+        let mut config = config_with_folders(&["config/", "config"]);
+        config.sync_target_filters.insert("config/".into(), "loader:fabric".into());
+        config.sync_target_filters.insert("config".into(), "version:1.21*".into());
+
+        reconcile(&mut config);
+
+        assert_eq!(config.sync_target_filters.len(), 1);
+        assert_eq!(config.sync_target_filters.get("config").map(|filter| &**filter), Some("version:1.21*"));
+    }
+
+    #[test]
+    fn file_shadowed_by_folder_keeps_settings() {
+        let mut config = config_with_folders(&["config"]);
+        config.sync_targets.files.insert("config".into());
+        config.sync_target_filters.insert("config".into(), "loader:fabric".into());
+
+        let merged = reconcile(&mut config);
+
+        assert_eq!(merged.len(), 1);
+        assert!(merged[0].is_file);
+        assert!(config.sync_targets.files.is_empty());
+        assert_eq!(config.sync_target_filters.get("config").map(|filter| &**filter), Some("loader:fabric"));
+    }
 }
@@ -124,11 +124,39 @@ impl BackendHandle {
         let _ = self.sender.send((message, Some(next_serial)));
     }
 
+    /// Sends a query built around a reply channel and returns the answer. The message is sent straight away,
+    /// so it stays ordered with messages sent afterwards even if the future is awaited later
+    pub fn request<T, F>(&self, message: F) -> impl Future<Output = Result<T, BackendError>> + use<T, F>
+    where
+        F: FnOnce(tokio::sync::oneshot::Sender<T>) -> MessageToBackend,
+    {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.send(message(send));
+        async move {
+            recv.await.map_err(|_| BackendError::NoResponse)
+        }
+    }
+
     pub fn is_closed(&self) -> bool {
         self.sender.is_closed()
     }
 }
 
+/// Why BackendHandle::request didn't get an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendError {
+    /// The backend dropped the reply channel, usually because it already reported an error for the query
+    NoResponse,
+}
+
+impl std::fmt::Display for BackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendError::NoResponse => write!(f, "the backend didn't respond"),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FrontendHandle {
     #[cfg(debug_assertions)]
@@ -1,11 +1,11 @@
 use std::{
-    collections::{BTreeMap, HashMap}, ffi::OsString, path::{Path, PathBuf}, sync::Arc
+    collections::{BTreeMap, HashMap}, ffi::OsString, path::{Path, PathBuf}, sync::Arc, time::SystemTime
 };
 
 use enumset::EnumSet;
 use rustc_hash::FxHashMap;
 use schema::{
-    backend_config::{BackendConfig, SyncTargets}, instance::{
+    backend_config::{BackendConfig, LinkStrategy, SyncConflictPolicy, SyncTargets, SyncedInstanceSetting}, instance::{
         InstanceConfiguration, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration,
         InstanceLinuxWrapperConfiguration, InstanceMemoryConfiguration, InstanceSystemLibrariesConfiguration, InstanceWrapperCommandConfiguration,
     }, loader::Loader, pandora_update::{UpdateManifest, UpdateManifestExe, UpdatePrompt}
@@ -55,6 +55,15 @@ pub enum MessageToBackend {
         id: InstanceID,
         disable_file_syncing: bool,
     },
+    SetInstanceSyncExclusion {
+        id: InstanceID,
+        target: Arc<str>,
+        excluded: bool,
+    },
+    SetInstanceSyncGroup {
+        id: InstanceID,
+        sync_group: Option<Arc<str>>,
+    },
     SetInstanceMemory {
         id: InstanceID,
         memory: InstanceMemoryConfiguration,
@@ -143,8 +152,31 @@ pub enum MessageToBackend {
         channel: tokio::sync::oneshot::Sender<ImportFromOtherLaunchers>,
     },
     GetSyncState {
+        /// Also measures the size of every synced folder, which can be slow
+        include_details: bool,
+        channel: tokio::sync::oneshot::Sender<SyncState>,
+    },
+    /// Replaces the synced copy of an enabled target with this instance's copy and pushes it to the other instances
+    PushFromInstance {
+        id: InstanceID,
+        target: Arc<str>,
+    },
+    /// The sync setup as JSON, see SyncConfigExport
+    ExportSyncConfig {
+        channel: tokio::sync::oneshot::Sender<String>,
+    },
+    /// Enables the targets from an exported sync setup. The current targets are disabled first if `replace` is set
+    ImportSyncConfig {
+        contents: Arc<str>,
+        replace: bool,
+    },
+    /// Reloads every instance from the instances folder, then returns the new sync state
+    RescanInstances {
         channel: tokio::sync::oneshot::Sender<SyncState>,
     },
+    GetSyncConfigSummary {
+        channel: tokio::sync::oneshot::Sender<String>,
+    },
     GetBackendConfiguration {
         channel: tokio::sync::oneshot::Sender<BackendConfig>,
     },
@@ -152,6 +184,55 @@ pub enum MessageToBackend {
         target: Arc<str>,
         is_file: bool,
         value: bool,
+        /// Move the contents of existing instance folders into the synced folder instead of refusing to enable
+        merge: bool,
+    },
+    RenameSyncTarget {
+        old: Arc<str>,
+        new: Arc<str>,
+        is_file: bool,
+    },
+    SetSyncTargetFilter {
+        target: Arc<str>,
+        filter: Option<Arc<str>>,
+    },
+    PreviewDisable {
+        target: Arc<str>,
+        channel: tokio::sync::oneshot::Sender<DisablePreview>,
+    },
+    PreviewSync {
+        direction: SyncDirection,
+        channel: tokio::sync::oneshot::Sender<Vec<(Arc<str>, ApplyPlan)>>,
+    },
+    SetSyncTargetVerified {
+        target: Arc<str>,
+        value: bool,
+    },
+    ApplySyncing {
+        direction: SyncDirection,
+    },
+    /// Applies syncing to one instance, or to all of them when `id` is None, and returns what changed in each
+    ApplySyncingWithReport {
+        id: Option<InstanceID>,
+        channel: tokio::sync::oneshot::Sender<Vec<(Arc<str>, SyncChangeReport)>>,
+    },
+    ResumeSyncOperation,
+    DiscardSyncOperation,
+    SetSyncingBatch {
+        targets: Vec<(Arc<str>, bool)>,
+        value: bool,
+        channel: tokio::sync::oneshot::Sender<SyncBatchResult>,
+    },
+    SetSyncedInstanceSetting {
+        setting: SyncedInstanceSetting,
+        value: bool,
+    },
+    MaintainStore {
+        channel: tokio::sync::oneshot::Sender<StoreMaintenanceReport>,
+    },
+    GetSyncLog {
+        limit: usize,
+        channel: tokio::sync::oneshot::Sender<Vec<SyncLogEntry>>,
     },
     CleanupOldLogFiles {
         instance: InstanceID,
@@ -176,6 +257,21 @@ pub enum MessageToBackend {
     SetOpenGameOutputAfterLaunching {
         value: bool,
     },
+    SetLinkStrategy {
+        strategy: LinkStrategy,
+    },
+    SetSyncingPaused {
+        value: bool,
+    },
+    SetWatchSync {
+        value: bool,
+    },
+    SetLinkCopyFallback {
+        value: bool,
+    },
+    SetSyncConflictPolicy {
+        policy: SyncConflictPolicy,
+    },
     CreateInstanceShortcut {
         id: InstanceID,
         path: PathBuf
@@ -252,6 +348,12 @@ pub enum MessageToFrontend {
     },
     Refresh,
     CloseModal,
+    /// How far enabling or applying a folder target got, sent while the Hardlink and Copy strategies copy files
+    SyncProgress {
+        target: Arc<str>,
+        done: usize,
+        total: usize,
+    },
     MoveInstanceToTop {
         id: InstanceID,
     },
@@ -271,19 +373,187 @@ pub struct LogFiles {
     pub total_gzipped_size: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct SyncTargetState {
     pub enabled: bool,
     pub is_file: bool,
     pub sync_count: usize,
     pub cannot_sync_count: usize,
+    pub matching_count: usize,
+    /// Instances that would match but have excluded this target, not included in matching_count
+    pub excluded_count: usize,
+    pub filter: Option<Arc<str>>,
+    pub verified: bool,
+    pub modified_files: Vec<Arc<str>>,
+    /// Other targets that share the same store folder as this one
+    pub aliases: Vec<Arc<str>>,
+    /// Size of the synced copy, only filled in when details were requested
+    pub total_bytes: u64,
+    /// Most recent modification inside the synced copy, only filled in when details were requested
+    pub last_modified: Option<SystemTime>,
+    /// How many files a pattern target currently matches, None for a target that is a single path
+    pub pattern_matches: Option<usize>,
+    /// An enabled target that only differs from this one by case, which the filesystem treats as the same name
+    pub case_collision: Option<Arc<str>>,
+    /// The most recent error while applying this target to an instance, kept until it applies cleanly again
+    pub last_error: Option<Arc<str>>,
+    /// Names of the instances counted in cannot_sync_count, only filled in when details were requested
+    pub cannot_sync_instances: Vec<Arc<str>>,
+    /// An instance copy made by the Hardlink or Copy strategy no longer matches the synced folder, only
+    /// filled in when details were requested. Always false for linked folders
+    pub drifted: bool,
 }
 
 #[derive(Debug)]
 pub struct SyncState {
     pub sync_folder: Arc<Path>,
+    /// Keyed by name. A name is only ever synced as a file or a folder since both would live at the same path in the
+    /// synced folder, so the filters, exclusions and errors of a target are keyed by name too
     pub targets: BTreeMap<Arc<str>, SyncTargetState>,
     pub total_count: usize,
+    pub synced_instance_settings: EnumSet<SyncedInstanceSetting>,
+    pub interrupted_operation: Option<InterruptedSyncOperation>,
+    pub paused: bool,
+    pub watch_sync: bool,
+    pub link_copy_fallback: bool,
+    /// Set when the synced folder overlaps an instance, syncing is refused until it's moved
+    pub location_error: Option<Arc<str>>,
+}
+
+impl SyncState {
+    pub fn target(&self, name: &str, is_file: bool) -> Option<&SyncTargetState> {
+        self.targets.get(name).filter(|state| state.is_file == is_file)
+    }
+}
+
+/// Which way file targets are copied when applying syncing. Folder targets are
+/// linked to the store, so the direction makes no difference for them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SyncDirection {
+    /// Merge options.txt and copy the most recently modified version of each file
+    #[default]
+    Auto,
+    /// Overwrite the files in every instance with the copy in the store
+    Push,
+    /// Copy the most recently modified version of each file into the store
+    Pull,
+}
+
+/// The outcome of a SetSyncingBatch, failed targets are paired with the reason they couldn't be changed
+#[derive(Debug, Default)]
+pub struct SyncBatchResult {
+    pub succeeded: Vec<Arc<str>>,
+    pub failed: Vec<(Arc<str>, Arc<str>)>,
+}
+
+/// What applying syncing to an instance actually changed
+#[derive(Debug, Default)]
+pub struct SyncChangeReport {
+    pub files_copied: Vec<Arc<str>>,
+    pub links_created: Vec<Arc<str>>,
+    pub links_removed: Vec<Arc<str>>,
+    /// Links whose synced folder had gone missing and was recreated
+    pub links_repaired: Vec<Arc<str>>,
+    pub options_changed: Vec<Arc<str>>,
+    /// Operations that failed, these don't stop the rest from being applied
+    pub errors: Vec<SyncError>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncError {
+    pub target: Arc<str>,
+    pub message: Arc<str>,
+}
+
+impl SyncChangeReport {
+    pub fn is_empty(&self) -> bool {
+        self.files_copied.is_empty() && self.links_created.is_empty() && self.links_removed.is_empty()
+            && self.links_repaired.is_empty() && self.options_changed.is_empty() && self.errors.is_empty()
+    }
+
+    pub fn append(&mut self, other: Self) {
+        self.files_copied.extend(other.files_copied);
+        self.links_created.extend(other.links_created);
+        self.links_removed.extend(other.links_removed);
+        self.links_repaired.extend(other.links_repaired);
+        self.options_changed.extend(other.options_changed);
+        self.errors.extend(other.errors);
+    }
+}
+
+/// A single change that applying syncing would make to an instance. Names are relative to .minecraft
+#[derive(Debug, Clone)]
+pub enum SyncOperation {
+    /// Removes a link to the synced folder for a folder that is no longer a sync target
+    RemoveLink { name: Arc<str>, path: Arc<Path>, target: Arc<Path> },
+    CreateLink { name: Arc<str>, path: Arc<Path>, target: Arc<Path> },
+    /// Recreates the missing synced folder that an existing link points to
+    RepairLink { name: Arc<str>, path: Arc<Path>, target: Arc<Path> },
+    /// Brings a folder created by the Hardlink or Copy link strategies up to date
+    RefreshFolder { name: Arc<str>, path: Arc<Path>, target: Arc<Path> },
+    /// `to_store` is true when the copy only updates the synced folder rather than the instance
+    CopyFile { name: Arc<str>, from: Arc<Path>, to: Arc<Path>, to_store: bool },
+    WriteOptions { path: Arc<Path>, store: Arc<Path>, contents: Arc<str>, changed_keys: Vec<Arc<str>> },
+    WriteServers { path: Arc<Path>, store: Arc<Path>, contents: Arc<[u8]> },
+    WriteHotbars { path: Arc<Path>, store: Arc<Path>, contents: Arc<[u8]> },
+}
+
+/// Everything applying syncing to an instance would do, computed without touching the filesystem
+#[derive(Debug, Clone, Default)]
+pub struct ApplyPlan {
+    pub operations: Vec<SyncOperation>,
+    /// Folder targets that won't be linked because the instance already has a real folder there
+    pub blocked_folders: Vec<Arc<str>>,
+}
+
+impl ApplyPlan {
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty() && self.blocked_folders.is_empty()
+    }
+}
+
+/// Instances that would be affected by disabling a folder target
+#[derive(Debug, Default)]
+pub struct DisablePreview {
+    pub links: Vec<Arc<str>>,
+    pub untouched: Vec<Arc<str>>,
+}
+
+#[derive(Debug)]
+pub struct InterruptedSyncOperation {
+    pub target: Arc<str>,
+    pub is_file: bool,
+    pub enabling: bool,
+    pub completed_count: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct StoreMaintenanceReport {
+    pub actions: Vec<Arc<str>>,
+    pub problems: Vec<Arc<str>>,
+    pub verified_links: usize,
+    pub reclaimed_bytes: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncLogOperation {
+    CopyFile,
+    CreateLink,
+    RemoveLink,
+    RepairLink,
+    ChangeOption,
+    Failed,
+}
+
+/// A single change recorded in the sync log, newest entries are returned first
+#[derive(Debug, Clone)]
+pub struct SyncLogEntry {
+    pub time: SystemTime,
+    pub instance: Arc<str>,
+    pub operation: SyncLogOperation,
+    pub target: Arc<str>,
+    /// Only set for failed operations
+    pub error: Option<Arc<str>>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
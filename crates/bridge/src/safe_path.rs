@@ -59,3 +59,98 @@ impl SafePath {
         self.0.file_name()
     }
 }
+
+/// Whether a file sync target is a pattern such as `config/*.json` rather than a single path
+pub fn is_path_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Whether every path a pattern can match would be a SafePath, wildcards aren't valid filename characters on Windows
+/// so they're swapped out before checking
+pub fn is_safe_path_pattern(pattern: &str) -> bool {
+    SafePath::new(&pattern.replace(['*', '?'], "_")).is_some()
+}
+
+/// Longest custom sync target accepted, leaving room for the instance folder within the usual 260 character path limit on Windows
+pub const MAX_SYNC_TARGET_LENGTH: usize = 160;
+
+const MAX_COMPONENT_LENGTH: usize = 255;
+
+/// Names Windows reserves for devices, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Why a name can't be used as a sync target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncTargetError {
+    Empty,
+    EmptySegment,
+    ParentDir,
+    ReservedName(Arc<str>),
+    TrailingDotOrSpace(Arc<str>),
+    InvalidCharacter(Arc<str>),
+    TooLong,
+    /// Only file targets can use `*` and `?`
+    FolderPattern,
+}
+
+impl std::fmt::Display for SyncTargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SyncTargetError::Empty => write!(f, "the path is empty"),
+            SyncTargetError::EmptySegment => write!(f, "the path contains an empty folder name"),
+            SyncTargetError::ParentDir => write!(f, "the path can't leave .minecraft using '..'"),
+            SyncTargetError::ReservedName(name) => write!(f, "'{}' is a reserved name on Windows", name),
+            SyncTargetError::TrailingDotOrSpace(name) => write!(f, "'{}' ends with a dot or space, which Windows doesn't allow", name),
+            SyncTargetError::InvalidCharacter(name) => write!(f, "'{}' contains a character that isn't allowed in file names", name),
+            SyncTargetError::TooLong => write!(f, "the path is longer than {} characters", MAX_SYNC_TARGET_LENGTH),
+            SyncTargetError::FolderPattern => write!(f, "only file targets can use * and ?"),
+        }
+    }
+}
+
+/// Checks a custom sync target before it's enabled, giving a specific reason instead of SafePath's None
+pub fn validate_sync_target(name: &str, is_file: bool) -> Result<(), SyncTargetError> {
+    let trimmed = name.trim_ascii().trim_end_matches('/');
+    if trimmed.is_empty() {
+        return Err(SyncTargetError::Empty);
+    }
+
+    let is_pattern = is_path_pattern(trimmed);
+    if is_pattern && !is_file {
+        return Err(SyncTargetError::FolderPattern);
+    }
+    if trimmed.chars().count() > MAX_SYNC_TARGET_LENGTH {
+        return Err(SyncTargetError::TooLong);
+    }
+
+    for segment in trimmed.split('/') {
+        match segment {
+            "" => return Err(SyncTargetError::EmptySegment),
+            "." => continue,
+            ".." => return Err(SyncTargetError::ParentDir),
+            _ => {},
+        }
+        if segment.len() > MAX_COMPONENT_LENGTH {
+            return Err(SyncTargetError::TooLong);
+        }
+
+        let stem = segment.split('.').next().unwrap_or(segment).trim_end();
+        if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+            return Err(SyncTargetError::ReservedName(segment.into()));
+        }
+        if segment.ends_with(['.', ' ']) {
+            return Err(SyncTargetError::TrailingDotOrSpace(segment.into()));
+        }
+
+        let checked = if is_pattern { segment.replace(['*', '?'], "_") } else { segment.to_string() };
+        if SafePath::new(&checked).is_none() {
+            return Err(SyncTargetError::InvalidCharacter(segment.into()));
+        }
+    }
+
+    Ok(())
+}
@@ -5,18 +5,20 @@ use gpui::Entity;
 use parking_lot::RwLock;
 
 use crate::entity::{
-    account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata
+    account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata, sync_progress::SyncProgressEntries
 };
 
 pub mod account;
 pub mod instance;
 pub mod metadata;
+pub mod sync_progress;
 
 #[derive(Clone)]
 pub struct DataEntities {
     pub instances: Entity<InstanceEntries>,
     pub metadata: Entity<FrontendMetadata>,
     pub accounts: Entity<AccountEntries>,
+    pub sync_progress: Entity<SyncProgressEntries>,
     pub backend_handle: BackendHandle,
     pub theme_folder: Arc<Path>,
     pub panic_messages: Arc<PanicMessages>,
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use gpui::{App, Entity};
+use rustc_hash::FxHashMap;
+
+/// Progress of the folder targets that are currently being copied, as done and total steps
+#[derive(Default)]
+pub struct SyncProgressEntries {
+    pub targets: FxHashMap<Arc<str>, (usize, usize)>,
+}
+
+impl SyncProgressEntries {
+    pub fn set(entity: &Entity<Self>, target: Arc<str>, done: usize, total: usize, cx: &mut App) {
+        entity.update(cx, |entries, cx| {
+            if done >= total {
+                entries.targets.remove(&target);
+            } else {
+                entries.targets.insert(target, (done, total));
+            }
+            cx.notify();
+        });
+    }
+}
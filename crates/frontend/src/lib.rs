@@ -16,7 +16,7 @@ use parking_lot::RwLock;
 
 use crate::{
     entity::{
-        DataEntities, PanicMessages, account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata
+        DataEntities, PanicMessages, account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata, sync_progress::SyncProgressEntries
     }, interface_config::InterfaceConfig, processor::Processor, root::{LauncherRoot, LauncherRootGlobal}
 };
 
@@ -161,11 +161,13 @@ pub fn start(
         });
         let metadata = cx.new(|_| FrontendMetadata::new(backend_handle.clone()));
         let accounts = cx.new(|_| AccountEntries::default());
+        let sync_progress = cx.new(|_| SyncProgressEntries::default());
         let data = DataEntities {
             instances,
             metadata,
             backend_handle,
             accounts,
+            sync_progress,
             theme_folder: theme_folder.into(),
             panic_messages: Arc::new(PanicMessages {
                 panic_message,
@@ -1,9 +1,9 @@
 use std::{path::Path, sync::Arc};
 
 use bridge::{handle::BackendHandle, message::MessageToBackend};
-use gpui::*;
-use gpui_component::{button::{Button, ButtonVariants}, checkbox::Checkbox, select::{SearchableVec, Select, SelectEvent, SelectState}, sheet::Sheet, spinner::Spinner, tab::{Tab, TabBar, TabVariant}, v_flex, ActiveTheme, IconName, Sizable, ThemeRegistry};
-use schema::backend_config::BackendConfig;
+use gpui::{prelude::*, *};
+use gpui_component::{button::{Button, ButtonGroup, ButtonVariants}, checkbox::Checkbox, select::{SearchableVec, Select, SelectEvent, SelectState}, sheet::Sheet, spinner::Spinner, tab::{Tab, TabBar, TabVariant}, v_flex, ActiveTheme, IconName, Selectable, Sizable, ThemeRegistry};
+use schema::backend_config::{BackendConfig, LinkStrategy, SyncConflictPolicy};
 
 use crate::{entity::DataEntities, interface_config::InterfaceConfig, ts};
 
@@ -171,6 +171,78 @@ impl Render for Settings {
                                 }
                             })))
                 ))
+                .child(crate::labelled(
+                    ts!("settings.link_strategy.title"),
+                    v_flex().gap_2()
+                        .child(ButtonGroup::new("link-strategy")
+                            .outline()
+                            // Junctions are created as symlinks outside of Windows, e.g. for a config copied from a Windows install
+                            .child(Button::new("link-symlink")
+                                .label(ts!("settings.link_strategy.symlink"))
+                                .selected(backend_config.link_strategy == LinkStrategy::Symlink
+                                    || (!cfg!(windows) && backend_config.link_strategy == LinkStrategy::Junction)))
+                            .when(cfg!(windows), |this| {
+                                this.child(Button::new("link-junction")
+                                    .label(ts!("settings.link_strategy.junction"))
+                                    .selected(backend_config.link_strategy == LinkStrategy::Junction))
+                            })
+                            .child(Button::new("link-hardlink")
+                                .label(ts!("settings.link_strategy.hardlink"))
+                                .selected(backend_config.link_strategy == LinkStrategy::Hardlink))
+                            .child(Button::new("link-copy")
+                                .label(ts!("settings.link_strategy.copy"))
+                                .selected(backend_config.link_strategy == LinkStrategy::Copy))
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, selected: &Vec<usize>, _, cx| {
+                                    let mut strategies = vec![LinkStrategy::Symlink];
+                                    if cfg!(windows) {
+                                        strategies.push(LinkStrategy::Junction);
+                                    }
+                                    strategies.push(LinkStrategy::Hardlink);
+                                    strategies.push(LinkStrategy::Copy);
+
+                                    if let Some(strategy) = selected.first().and_then(|index| strategies.get(*index)) {
+                                        backend_handle.send(MessageToBackend::SetLinkStrategy {
+                                            strategy: *strategy
+                                        });
+                                        settings.update_backend_configuration(cx);
+                                    }
+                                }
+                            })))
+                        .child(ts!("settings.link_strategy.description"))
+                ))
+                .child(crate::labelled(
+                    ts!("settings.conflict_policy.title"),
+                    v_flex().gap_2()
+                        .child(ButtonGroup::new("conflict-policy")
+                            .outline()
+                            .child(Button::new("policy-latest")
+                                .label(ts!("settings.conflict_policy.latest_mtime"))
+                                .selected(backend_config.sync_conflict_policy == SyncConflictPolicy::LatestMtime))
+                            .child(Button::new("policy-majority")
+                                .label(ts!("settings.conflict_policy.majority_hash"))
+                                .selected(backend_config.sync_conflict_policy == SyncConflictPolicy::MajorityHash))
+                            .child(Button::new("policy-largest")
+                                .label(ts!("settings.conflict_policy.largest_file"))
+                                .selected(backend_config.sync_conflict_policy == SyncConflictPolicy::LargestFile))
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, selected: &Vec<usize>, _, cx| {
+                                    let policy = match selected.first() {
+                                        Some(0) => SyncConflictPolicy::LatestMtime,
+                                        Some(1) => SyncConflictPolicy::MajorityHash,
+                                        Some(2) => SyncConflictPolicy::LargestFile,
+                                        _ => return,
+                                    };
+                                    backend_handle.send(MessageToBackend::SetSyncConflictPolicy {
+                                        policy
+                                    });
+                                    settings.update_backend_configuration(cx);
+                                }
+                            })))
+                        .child(ts!("settings.conflict_policy.description"))
+                ))
         } else {
             div = div.child(Spinner::new().large());
         }
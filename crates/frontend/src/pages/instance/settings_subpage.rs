@@ -32,6 +32,7 @@ pub struct InstanceSettingsSubpage {
     loader_versions_state: TypelessFrontendMetadataResult,
     loader_version_select_state: Entity<SelectState<SearchableVec<&'static str>>>,
     disable_file_syncing: bool,
+    sync_group_input_state: Entity<InputState>,
 
     memory_override_enabled: bool,
     memory_min_input_state: Entity<InputState>,
@@ -79,6 +80,7 @@ impl InstanceSettingsSubpage {
         let loader = entry.configuration.loader;
         let preferred_loader_version = entry.configuration.preferred_loader_version.map(|s| s.as_str()).unwrap_or("Latest");
         let disable_file_syncing = entry.configuration.disable_file_syncing;
+        let sync_group = entry.configuration.sync_group.as_deref().unwrap_or_default().to_string();
 
         let memory = entry.configuration.memory.unwrap_or_default();
         let wrapper_command = entry.configuration.wrapper_command.clone().unwrap_or_default();
@@ -129,6 +131,13 @@ impl InstanceSettingsSubpage {
         });
         cx.subscribe(&loader_version_select_state, Self::on_loader_version_selected).detach();
 
+        let sync_group_input_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(ts!("instance.sync.group_placeholder"))
+                .default_value(sync_group)
+        });
+        cx.subscribe(&sync_group_input_state, Self::on_sync_group_changed).detach();
+
         let memory_min_input_state = cx.new(|cx| {
             InputState::new(window, cx).default_value(memory.min.to_string())
         });
@@ -161,6 +170,7 @@ impl InstanceSettingsSubpage {
             loader_select_state,
             loader_version_select_state,
             disable_file_syncing,
+            sync_group_input_state,
             memory_override_enabled: memory.enabled,
             memory_min_input_state,
             memory_max_input_state,
@@ -458,6 +468,22 @@ impl InstanceSettingsSubpage {
         }
     }
 
+    pub fn on_sync_group_changed(
+        &mut self,
+        _: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Change = event {
+            let sync_group = self.sync_group_input_state.read(cx).value();
+            let sync_group = sync_group.trim();
+            self.backend_handle.send(MessageToBackend::SetInstanceSyncGroup {
+                id: self.instance_id,
+                sync_group: (!sync_group.is_empty()).then(|| sync_group.into()),
+            });
+        }
+    }
+
     fn get_memory_configuration(&self, cx: &App) -> InstanceMemoryConfiguration {
         let min = self.memory_min_input_state.read(cx).value().parse::<u32>().unwrap_or(0);
         let max = self.memory_max_input_state.read(cx).value().parse::<u32>().unwrap_or(0);
@@ -688,13 +714,16 @@ impl Render for InstanceSettingsSubpage {
             ))
             .child(crate::labelled(
                 ts!("instance.sync.label"),
-                Checkbox::new("syncing").label(ts!("instance.sync.disable_syncing")).checked(self.disable_file_syncing).on_click(cx.listener(|page, value, _, _| {
-                    page.disable_file_syncing = *value;
-                    page.backend_handle.send(MessageToBackend::SetInstanceDisableFileSyncing {
-                        id: page.instance_id,
-                        disable_file_syncing: *value
-                    });
-                }))
+                v_flex()
+                    .gap_1()
+                    .child(Checkbox::new("syncing").label(ts!("instance.sync.disable_syncing")).checked(self.disable_file_syncing).on_click(cx.listener(|page, value, _, _| {
+                        page.disable_file_syncing = *value;
+                        page.backend_handle.send(MessageToBackend::SetInstanceDisableFileSyncing {
+                            id: page.instance_id,
+                            disable_file_syncing: *value
+                        });
+                    })))
+                    .child(Input::new(&self.sync_group_input_state))
             ));
 
         let runtime_content = v_flex()
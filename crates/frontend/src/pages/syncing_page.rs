@@ -1,123 +1,961 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, sync::Arc, time::SystemTime};
 
-use bridge::{handle::BackendHandle, message::{MessageToBackend, SyncState}, safe_path::SafePath};
+use bridge::{handle::BackendHandle, instance::InstanceID, message::{ApplyPlan, DisablePreview, MessageToBackend, StoreMaintenanceReport, SyncDirection, SyncLogEntry, SyncLogOperation, SyncOperation, SyncState}, safe_path::{validate_sync_target, SyncTargetError, MAX_SYNC_TARGET_LENGTH}};
 use enumset::EnumSet;
+use schema::backend_config::SyncedInstanceSetting;
 use gpui::{prelude::*, *};
 use gpui_component::{
-    button::{Button, ButtonVariants}, checkbox::Checkbox, h_flex, input::{Input, InputState}, scroll::ScrollableElement, spinner::Spinner, tooltip::Tooltip, v_flex, ActiveTheme as _, Disableable, Icon, IconName, Sizable
+    button::{Button, ButtonVariants}, checkbox::Checkbox, IndexPath, h_flex, input::{Input, InputEvent, InputState}, notification::NotificationType, scroll::ScrollableElement, select::{Select, SelectEvent, SelectState}, spinner::Spinner, tooltip::Tooltip, v_flex, ActiveTheme as _, Disableable, Icon, IconName, Sizable, WindowExt
 };
 use once_cell::sync::Lazy;
 use rustc_hash::FxHashSet;
 
-use crate::{entity::DataEntities, ts, ui};
+use crate::{component::instance_dropdown::InstanceDropdown, entity::{instance::{InstanceAddedEvent, InstanceEntries, InstanceEntry, InstanceModifiedEvent, InstanceRemovedEvent}, sync_progress::SyncProgressEntries, DataEntities}, ts, ui};
 
 pub struct SyncingPage {
     backend_handle: BackendHandle,
+    instances: Entity<InstanceEntries>,
+    sync_progress: Entity<SyncProgressEntries>,
     sync_state: Option<SyncState>,
     pending: FxHashSet<Arc<str>>,
     loading: FxHashSet<Arc<str>>,
+    /// Targets in a SetSyncingBatch that hasn't finished yet, they stay loading until it does
+    batched: FxHashSet<Arc<str>>,
     custom_input_state: Entity<InputState>,
+    search_input_state: Entity<InputState>,
+    override_instance_select: Entity<SelectState<InstanceDropdown>>,
+    maintaining: bool,
+    rescanning: bool,
     _get_sync_state_task: Task<()>,
+    _get_sync_details_task: Task<()>,
+    _maintain_store_task: Task<()>,
+    _sync_log_task: Task<()>,
+    _rescan_task: Task<()>,
+    _copy_summary_task: Task<()>,
+    _export_config_task: Task<()>,
+    _preview_disable_task: Task<()>,
+    _preview_sync_task: Task<()>,
+    _set_syncing_batch_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl SyncingPage {
     pub fn new(data: &DataEntities, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let override_instance_select = Self::create_override_instance_select(&data.instances, None, window, cx);
+        let search_input_state = cx.new(|cx| InputState::new(window, cx).placeholder(ts!("instance.sync.search_placeholder")).clean_on_escape());
+
+        let _subscriptions = vec![
+            cx.subscribe(&search_input_state, |_, _, _: &InputEvent, cx| {
+                cx.notify();
+            }),
+            cx.subscribe_in::<_, InstanceAddedEvent>(&data.instances, window, |page, _, _, window, cx| {
+                page.recreate_override_instance_select(window, cx);
+            }),
+            cx.subscribe_in::<_, InstanceRemovedEvent>(&data.instances, window, |page, _, _, window, cx| {
+                page.recreate_override_instance_select(window, cx);
+            }),
+            cx.subscribe::<_, InstanceModifiedEvent>(&data.instances, |_, _, _, cx| {
+                cx.notify();
+            }),
+            cx.observe(&data.sync_progress, |_, _, cx| {
+                cx.notify();
+            }),
+        ];
+
         let mut page = Self {
             backend_handle: data.backend_handle.clone(),
+            instances: data.instances.clone(),
+            sync_progress: data.sync_progress.clone(),
             sync_state: None,
             pending: FxHashSet::default(),
+            batched: FxHashSet::default(),
             loading: FxHashSet::default(),
-            custom_input_state: cx.new(|cx| InputState::new(window, cx)),
+            custom_input_state: cx.new(|cx| InputState::new(window, cx).placeholder(ts!("instance.sync.custom_placeholder"))),
+            search_input_state,
+            override_instance_select,
+            maintaining: false,
+            rescanning: false,
             _get_sync_state_task: Task::ready(()),
+            _get_sync_details_task: Task::ready(()),
+            _maintain_store_task: Task::ready(()),
+            _sync_log_task: Task::ready(()),
+            _rescan_task: Task::ready(()),
+            _copy_summary_task: Task::ready(()),
+            _export_config_task: Task::ready(()),
+            _preview_disable_task: Task::ready(()),
+            _preview_sync_task: Task::ready(()),
+            _set_syncing_batch_task: Task::ready(()),
+            _subscriptions,
         };
 
         page.update_sync_state(cx);
 
         page
     }
+
+    fn create_override_instance_select(instances: &Entity<InstanceEntries>, selected: Option<InstanceID>, window: &mut Window, cx: &mut Context<Self>) -> Entity<SelectState<InstanceDropdown>> {
+        let entries: Arc<[InstanceEntry]> = instances.read(cx).entries.values().map(|entry| entry.read(cx).clone()).collect();
+        let selected_index = selected.and_then(|id| entries.iter().position(|entry| entry.id == id));
+
+        let select = InstanceDropdown::create(entries, window, cx);
+        if let Some(selected_index) = selected_index {
+            select.update(cx, |select, cx| {
+                select.set_selected_index(Some(IndexPath::default().row(selected_index)), window, cx);
+            });
+        }
+
+        cx.subscribe(&select, |_, _, _: &SelectEvent<InstanceDropdown>, cx| {
+            cx.notify();
+        }).detach();
+
+        select
+    }
+
+    fn recreate_override_instance_select(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let selected = self.override_instance_select.read(cx).selected_value().map(|entry| entry.id);
+        self.override_instance_select = Self::create_override_instance_select(&self.instances, selected, window, cx);
+        cx.notify();
+    }
 }
 
 impl SyncingPage {
     pub fn update_sync_state(&mut self, cx: &mut Context<Self>) {
-        let (send, recv) = tokio::sync::oneshot::channel();
+        let request = self.backend_handle.request(|channel| MessageToBackend::GetSyncState {
+            include_details: false,
+            channel,
+        });
         self._get_sync_state_task = cx.spawn(async move |page, cx| {
-            let Ok(result): Result<SyncState, _> = recv.await else {
+            let Ok(result) = request.await else {
                 return;
             };
             let _ = page.update(cx, move |page, cx| {
                 page.loading.retain(|loading| !page.pending.contains(loading));
                 page.pending = FxHashSet::default();
-                page.sync_state = Some(result);
-                cx.notify();
+                page.set_sync_state(result, cx);
 
-                if !page.loading.is_empty() {
-                    page.pending = page.loading.clone();
+                if page.loading.iter().any(|loading| !page.batched.contains(loading)) {
+                    page.pending = page.loading.difference(&page.batched).cloned().collect();
                     page.update_sync_state(cx);
                 }
             });
         });
+    }
+
+    /// Shows a new sync state straight away, keeping the sizes from the previous one until they are measured again
+    fn set_sync_state(&mut self, mut sync_state: SyncState, cx: &mut Context<Self>) {
+        if let Some(previous) = &self.sync_state {
+            for (name, state) in sync_state.targets.iter_mut() {
+                if let Some(previous) = previous.targets.get(name) && previous.is_file == state.is_file {
+                    state.total_bytes = previous.total_bytes;
+                    state.last_modified = previous.last_modified;
+                    state.cannot_sync_instances = previous.cannot_sync_instances.clone();
+                    state.drifted = previous.drifted;
+                }
+            }
+        }
+        self.sync_state = Some(sync_state);
+        cx.notify();
+        self.load_sync_details(cx);
+    }
+
+    fn load_sync_details(&mut self, cx: &mut Context<Self>) {
+        let request = self.backend_handle.request(|channel| MessageToBackend::GetSyncState {
+            include_details: true,
+            channel,
+        });
+        self._get_sync_details_task = cx.spawn(async move |page, cx| {
+            let Ok(detailed) = request.await else {
+                return;
+            };
+            let _ = page.update(cx, move |page, cx| {
+                let Some(sync_state) = &mut page.sync_state else {
+                    return;
+                };
+                for (name, state) in sync_state.targets.iter_mut() {
+                    if let Some(detailed) = detailed.targets.get(name) && detailed.is_file == state.is_file {
+                        state.total_bytes = detailed.total_bytes;
+                        state.last_modified = detailed.last_modified;
+                        state.cannot_sync_instances = detailed.cannot_sync_instances.clone();
+                        state.drifted = detailed.drifted;
+                    }
+                }
+                cx.notify();
+            });
+        });
+    }
+
+    pub fn rescan_instances(&mut self, cx: &mut Context<Self>) {
+        self.rescanning = true;
+
+        let request = self.backend_handle.request(|channel| MessageToBackend::RescanInstances { channel });
+        self._rescan_task = cx.spawn(async move |page, cx| {
+            let result = request.await;
+            let _ = page.update(cx, move |page, cx| {
+                page.rescanning = false;
+                if let Ok(result) = result {
+                    // Messages are handled in order, so every change sent before the rescan is already
+                    // reflected in this state and nothing should be left loading
+                    page.loading.clear();
+                    page.pending.clear();
+                    page._get_sync_state_task = Task::ready(());
+                    page.set_sync_state(result, cx);
+                }
+                cx.notify();
+            });
+        });
+        cx.notify();
+    }
+
+    pub fn maintain_store(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.maintaining = true;
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._maintain_store_task = cx.spawn_in(window, async move |page, cx| {
+            let result: Result<StoreMaintenanceReport, _> = recv.await;
+            let _ = page.update_in(cx, move |page, window, cx| {
+                page.maintaining = false;
+                if let Ok(report) = result {
+                    open_maintenance_report(report, window, cx);
+                }
+                page.update_sync_state(cx);
+                cx.notify();
+            });
+        });
+
+        self.backend_handle.send(MessageToBackend::MaintainStore {
+            channel: send,
+        });
+    }
+
+    pub fn show_sync_log(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._sync_log_task = cx.spawn_in(window, async move |page, cx| {
+            let result: Result<Vec<SyncLogEntry>, _> = recv.await;
+            let _ = page.update_in(cx, move |_, window, cx| {
+                if let Ok(entries) = result {
+                    open_sync_log(entries, window, cx);
+                }
+            });
+        });
 
-        self.backend_handle.send(MessageToBackend::GetSyncState {
+        self.backend_handle.send(MessageToBackend::GetSyncLog {
+            limit: SYNC_LOG_LIMIT,
             channel: send,
         });
     }
 
+    pub fn set_syncing(&mut self, name: Arc<str>, is_file: bool, value: bool, cx: &mut Context<Self>) {
+        self.send_set_syncing(name, is_file, value, false, cx);
+    }
+
+    fn send_set_syncing(&mut self, name: Arc<str>, is_file: bool, value: bool, merge: bool, cx: &mut Context<Self>) {
+        self.backend_handle.send(MessageToBackend::SetSyncing {
+            target: name.clone(),
+            is_file,
+            value,
+            merge,
+        });
+
+        self.loading.insert(name.clone());
+        if self.pending.is_empty() {
+            self.pending.insert(name);
+            self.update_sync_state(cx);
+        }
+    }
+
+    pub fn confirm_merge(&mut self, name: Arc<str>, count: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let page = cx.entity().downgrade();
+        let title = ts!("instance.sync.merge.title", name = name);
+        let description = ts!("instance.sync.merge.description", num = count, name = name);
+        window.open_dialog(cx, move |modal, _, _| {
+            let name = name.clone();
+            let page = page.clone();
+            modal
+                .title(title.clone())
+                .child(v_flex()
+                    .gap_2()
+                    .child(description.clone())
+                    .child(Button::new("confirm").success().label(ts!("instance.sync.merge.confirm")).on_click(move |_, window, cx| {
+                        let name = name.clone();
+                        _ = page.update(cx, |page, cx| {
+                            page.send_set_syncing(name, false, true, true, cx);
+                        });
+                        window.close_dialog(cx);
+                    })))
+        });
+    }
+
+    pub fn confirm_disable_all(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let page = cx.entity().downgrade();
+        window.open_dialog(cx, move |modal, _, _| {
+            let page = page.clone();
+            modal
+                .title(ts!("instance.sync.disable_all.title"))
+                .child(v_flex()
+                    .gap_2()
+                    .child(ts!("instance.sync.disable_all.description"))
+                    .child(Button::new("confirm").danger().label(ts!("instance.sync.disable_all.label")).on_click(move |_, window, cx| {
+                        _ = page.update(cx, |page, cx| {
+                            page.disable_all(cx);
+                        });
+                        window.close_dialog(cx);
+                    })))
+        });
+    }
+
+    pub fn disable_all(&mut self, cx: &mut Context<Self>) {
+        let Some(sync_state) = &self.sync_state else {
+            return;
+        };
+
+        let targets: Vec<(Arc<str>, bool)> = sync_state.targets.iter()
+            .filter(|(_, state)| state.enabled)
+            .map(|(name, state)| (name.clone(), state.is_file))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        self.set_syncing_batch(targets, false, cx);
+    }
+
+    /// Enables the files and folders from the named sections that aren't enabled yet
+    pub fn enable_recommended(&mut self, cx: &mut Context<Self>) {
+        let Some(sync_state) = &self.sync_state else {
+            return;
+        };
+
+        let targets: Vec<(Arc<str>, bool)> = RECOMMENDED_SYNC_TARGETS.iter()
+            .filter(|(name, is_file)| !sync_state.target(name, *is_file).is_some_and(|state| state.enabled))
+            .map(|(name, is_file)| (Arc::from(*name), *is_file))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        self.set_syncing_batch(targets, true, cx);
+    }
+
+    /// Changes several targets in one message, the page refreshes once the whole batch is done
+    fn set_syncing_batch(&mut self, targets: Vec<(Arc<str>, bool)>, value: bool, cx: &mut Context<Self>) {
+        if !self.batched.is_empty() {
+            return;
+        }
+        self.loading.extend(targets.iter().map(|(name, _)| name.clone()));
+        self.batched.extend(targets.iter().map(|(name, _)| name.clone()));
+
+        let request = self.backend_handle.request(|channel| MessageToBackend::SetSyncingBatch {
+            targets,
+            value,
+            channel,
+        });
+        // Only one batch runs at a time, so this never replaces a task that's still going
+        self._set_syncing_batch_task = cx.spawn(async move |page, cx| {
+            // Failures are reported by the backend, either way the targets are done loading
+            _ = request.await;
+            _ = page.update(cx, move |page, cx| {
+                for name in std::mem::take(&mut page.batched) {
+                    page.loading.remove(&name);
+                }
+                page.update_sync_state(cx);
+            });
+        });
+    }
+
+    pub fn open_filter_dialog(&mut self, name: Arc<str>, current: Option<Arc<str>>, window: &mut Window, cx: &mut Context<Self>) {
+        let input_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder(ts!("instance.sync.filter.placeholder"))
+                .default_value(current.as_deref().unwrap_or_default().to_string())
+        });
+
+        let page = cx.entity().downgrade();
+        let title = ts!("instance.sync.filter.title", name = name);
+        window.open_dialog(cx, move |modal, _, _| {
+            let name = name.clone();
+            let page = page.clone();
+            let input_state = input_state.clone();
+            modal
+                .title(title.clone())
+                .child(v_flex()
+                    .gap_2()
+                    .child(ts!("instance.sync.filter.description"))
+                    .child(Input::new(&input_state))
+                    .child(Button::new("save").success().label(ts!("instance.sync.filter.save")).on_click(move |_, window, cx| {
+                        let value = input_state.read(cx).value();
+                        let value = value.trim_ascii();
+                        let filter = if value.is_empty() {
+                            None
+                        } else {
+                            Some(value.into())
+                        };
+                        _ = page.update(cx, |page, cx| {
+                            page.backend_handle.send(MessageToBackend::SetSyncTargetFilter {
+                                target: name.clone(),
+                                filter,
+                            });
+                            page.update_sync_state(cx);
+                        });
+                        window.close_dialog(cx);
+                    })))
+        });
+    }
+
+    pub fn apply_syncing(&mut self, direction: SyncDirection, cx: &mut Context<Self>) {
+        self.backend_handle.send(MessageToBackend::ApplySyncing { direction });
+        self.update_sync_state(cx);
+    }
+
+    pub fn preview_sync(&mut self, direction: SyncDirection, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._preview_sync_task = cx.spawn_in(window, async move |page, cx| {
+            let Ok(plans): Result<Vec<(Arc<str>, ApplyPlan)>, _> = recv.await else {
+                return;
+            };
+            let _ = page.update_in(cx, move |page, window, cx| {
+                if plans.is_empty() {
+                    page.apply_syncing(direction, cx);
+                    return;
+                }
+
+                let page = cx.entity().downgrade();
+                let instances: Vec<(SharedString, Vec<SharedString>, Vec<SharedString>)> = plans.into_iter().map(|(name, plan)| {
+                    let operations = plan.operations.iter().map(describe_sync_operation).collect();
+                    let blocked = plan.blocked_folders.iter().map(|name| ts!("instance.sync.preview_sync.blocked", name = name)).collect();
+                    (name.into(), operations, blocked)
+                }).collect();
+
+                window.open_dialog(cx, move |modal, _, cx| {
+                    let page = page.clone();
+                    let mut content = v_flex().gap_2();
+                    for (name, operations, blocked) in &instances {
+                        content = content
+                            .child(div().border_b_1().border_color(cx.theme().border).child(name.clone()))
+                            .children(operations.iter().cloned())
+                            .children(blocked.iter().map(|blocked| div().text_color(cx.theme().warning).child(blocked.clone())));
+                    }
+                    let content = content
+                        .child(Button::new("confirm").success().label(ts!("instance.sync.preview_sync.confirm")).on_click(move |_, window, cx| {
+                            _ = page.update(cx, |page, cx| {
+                                page.apply_syncing(direction, cx);
+                            });
+                            window.close_dialog(cx);
+                        }));
+
+                    modal.title(ts!("instance.sync.preview_sync.title")).child(content)
+                });
+            });
+        });
+
+        self.backend_handle.send(MessageToBackend::PreviewSync {
+            direction,
+            channel: send,
+        });
+    }
+
+    pub fn preview_disable(&mut self, name: Arc<str>, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._preview_disable_task = cx.spawn_in(window, async move |page, cx| {
+            let Ok(preview): Result<DisablePreview, _> = recv.await else {
+                return;
+            };
+            let _ = page.update_in(cx, move |page, window, cx| {
+                if preview.links.is_empty() && preview.untouched.is_empty() {
+                    page.set_syncing(name, false, false, cx);
+                    return;
+                }
+
+                let page = cx.entity().downgrade();
+                let links: Vec<SharedString> = preview.links.into_iter().map(SharedString::from).collect();
+                let untouched: Vec<SharedString> = preview.untouched.into_iter().map(SharedString::from).collect();
+                let title = ts!("instance.sync.preview_disable.title", name = name);
+
+                window.open_dialog(cx, move |modal, _, cx| {
+                    let name = name.clone();
+                    let page = page.clone();
+                    let content = v_flex()
+                        .gap_2()
+                        .when(!links.is_empty(), |content| {
+                            content
+                                .child(div().border_b_1().border_color(cx.theme().border).child(ts!("instance.sync.preview_disable.links")))
+                                .children(links.iter().cloned())
+                        })
+                        .when(!untouched.is_empty(), |content| {
+                            content
+                                .child(div().border_b_1().border_color(cx.theme().border).child(ts!("instance.sync.preview_disable.untouched")))
+                                .children(untouched.iter().cloned())
+                        })
+                        .child(Button::new("confirm").danger().label(ts!("instance.sync.preview_disable.confirm")).on_click(move |_, window, cx| {
+                            let name = name.clone();
+                            _ = page.update(cx, |page, cx| {
+                                page.set_syncing(name, false, false, cx);
+                            });
+                            window.close_dialog(cx);
+                        }));
+
+                    modal.title(title.clone()).child(content)
+                });
+            });
+        });
+
+        self.backend_handle.send(MessageToBackend::PreviewDisable {
+            target: name,
+            channel: send,
+        });
+    }
+
+    pub fn copy_sync_config_summary(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._copy_summary_task = cx.spawn_in(window, async move |page, cx| {
+            let Ok(summary): Result<String, _> = recv.await else {
+                return;
+            };
+            let _ = page.update_in(cx, move |_, window, cx| {
+                cx.write_to_clipboard(ClipboardItem::new_string(summary));
+                window.push_notification((NotificationType::Success, ts!("instance.sync.copy_summary.copied")), cx);
+            });
+        });
+
+        self.backend_handle.send(MessageToBackend::GetSyncConfigSummary {
+            channel: send,
+        });
+    }
+
+    pub fn confirm_push_from_instance(&mut self, id: InstanceID, target: Arc<str>, window: &mut Window, cx: &mut Context<Self>) {
+        let instance_name = self.instances.read(cx).entries.get(&id).map(|instance| instance.read(cx).name.clone()).unwrap_or_default();
+        let page = cx.entity().downgrade();
+        let title = ts!("instance.sync.push_from_instance.title", name = target);
+        let description = ts!("instance.sync.push_from_instance.description", name = target, instance = instance_name);
+        window.open_dialog(cx, move |modal, _, _| {
+            let target = target.clone();
+            let page = page.clone();
+            modal
+                .title(title.clone())
+                .child(v_flex()
+                    .gap_2()
+                    .child(description.clone())
+                    .child(Button::new("confirm").danger().label(ts!("instance.sync.push_from_instance.confirm")).on_click(move |_, window, cx| {
+                        _ = page.update(cx, |page, cx| {
+                            page.backend_handle.send(MessageToBackend::PushFromInstance {
+                                id,
+                                target: target.clone(),
+                            });
+                            page.update_sync_state(cx);
+                        });
+                        window.close_dialog(cx);
+                    })))
+        });
+    }
+
+    pub fn export_sync_config(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._export_config_task = cx.spawn_in(window, async move |page, cx| {
+            let Ok(json): Result<String, _> = recv.await else {
+                return;
+            };
+            let _ = page.update_in(cx, move |_, window, cx| {
+                cx.write_to_clipboard(ClipboardItem::new_string(json));
+                window.push_notification((NotificationType::Success, ts!("instance.sync.transfer.exported")), cx);
+            });
+        });
+
+        self.backend_handle.send(MessageToBackend::ExportSyncConfig {
+            channel: send,
+        });
+    }
+
+    pub fn open_import_dialog(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let input_state = cx.new(|cx| {
+            InputState::new(window, cx)
+                .auto_grow(4, 16)
+                .placeholder(ts!("instance.sync.transfer.placeholder"))
+        });
+
+        let page = cx.entity().downgrade();
+        window.open_dialog(cx, move |modal, _, _| {
+            let import = |id: &'static str, label: SharedString, replace: bool| {
+                let page = page.clone();
+                let input_state = input_state.clone();
+                Button::new(id).label(label).on_click(move |_, window, cx| {
+                    let contents = input_state.read(cx).value();
+                    if contents.trim_ascii().is_empty() {
+                        return;
+                    }
+                    _ = page.update(cx, |page, cx| {
+                        page.backend_handle.send(MessageToBackend::ImportSyncConfig {
+                            contents: Arc::from(&*contents),
+                            replace,
+                        });
+                        page.update_sync_state(cx);
+                    });
+                    window.close_dialog(cx);
+                })
+            };
+
+            modal
+                .title(ts!("instance.sync.transfer.import_title"))
+                .child(v_flex()
+                    .gap_2()
+                    .child(ts!("instance.sync.transfer.import_description"))
+                    .child(Input::new(&input_state))
+                    .child(h_flex()
+                        .gap_2()
+                        .child(import("import", ts!("instance.sync.transfer.import_add"), false).success())
+                        .child(import("replace", ts!("instance.sync.transfer.import_replace"), true).danger())))
+        });
+    }
+
+    fn render_instance_overrides(&self, sync_state: &SyncState, cx: &mut Context<Self>) -> Div {
+        let mut content = v_flex()
+            .gap_3()
+            .child(ts!("instance.sync.overrides.description"))
+            .child(div().max_w_128().child(Select::new(&self.override_instance_select).placeholder(ts!("instance.none_selected")).title_prefix(format!("{}: ", ts!("instance.label")))));
+
+        let Some(id) = self.override_instance_select.read(cx).selected_value().map(|entry| entry.id) else {
+            return content;
+        };
+        let Some(instance) = self.instances.read(cx).entries.get(&id) else {
+            return content;
+        };
+        let configuration = instance.read(cx).configuration.clone();
+
+        if configuration.disable_file_syncing {
+            return content.child(ts!("instance.sync.overrides.syncing_disabled"));
+        }
+
+        let mut any_enabled = false;
+        for (name, state) in sync_state.targets.iter() {
+            if !state.enabled {
+                continue;
+            }
+            any_enabled = true;
+
+            let label = if state.is_file {
+                ts!("instance.sync.sync_name_file", name = name)
+            } else {
+                ts!("instance.sync.sync_name_folder", name = name)
+            };
+            let excluded = configuration.sync_exclusions.contains(name);
+
+            let backend_handle = self.backend_handle.clone();
+            let target = name.clone();
+            let name = name.clone();
+            content = content.child(h_flex()
+                .gap_2()
+                .child(Checkbox::new(SharedString::from(format!("override-{}", name)))
+                    .label(label)
+                    .checked(!excluded)
+                    .on_click(move |value, _, _| {
+                        backend_handle.send(MessageToBackend::SetInstanceSyncExclusion {
+                            id,
+                            target: name.clone(),
+                            excluded: !*value,
+                        });
+                    }))
+                .when(!excluded, |this| {
+                    this.child(Button::new(SharedString::from(format!("push-from-{}", target)))
+                        .small()
+                        .label(ts!("instance.sync.push_from_instance.label"))
+                        .tooltip(ts!("instance.sync.push_from_instance.tooltip"))
+                        .on_click(cx.listener(move |page, _, window, cx| {
+                            page.confirm_push_from_instance(id, target.clone(), window, cx);
+                        })))
+                }));
+        }
+
+        if !any_enabled {
+            content = content.child(ts!("instance.sync.overrides.no_targets"));
+        }
+
+        content
+    }
+
+    fn render_instance_settings(&self, sync_state: &SyncState, cx: &mut Context<Self>) -> Div {
+        let settings = [
+            (SyncedInstanceSetting::JvmFlags, ts!("instance.sync.instance_settings.jvm_flags")),
+            (SyncedInstanceSetting::JvmBinary, ts!("instance.sync.instance_settings.jvm_binary")),
+            (SyncedInstanceSetting::WrapperCommand, ts!("instance.sync.instance_settings.wrapper_command")),
+            (SyncedInstanceSetting::LinuxWrapper, ts!("instance.sync.instance_settings.linux_wrapper")),
+            (SyncedInstanceSetting::SystemLibraries, ts!("instance.sync.instance_settings.system_libraries")),
+        ];
+
+        v_flex()
+            .gap_3()
+            .child(ts!("instance.sync.instance_settings.description"))
+            .children(settings.into_iter().map(|(setting, label)| {
+                Checkbox::new(SharedString::from(format!("instance-setting-{:?}", setting)))
+                    .label(label)
+                    .checked(sync_state.synced_instance_settings.contains(setting))
+                    .on_click(cx.listener(move |page, value: &bool, _, cx| {
+                        page.backend_handle.send(MessageToBackend::SetSyncedInstanceSetting {
+                            setting,
+                            value: *value,
+                        });
+                        page.update_sync_state(cx);
+                    }))
+            }))
+    }
+
+    fn add_custom_target(&mut self, is_file: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let input = self.custom_input_state.read(cx).value();
+        let input = input.as_str().trim_ascii().trim_end_matches('/');
+        match validate_sync_target(input, is_file) {
+            Ok(()) => self.set_syncing(input.into(), is_file, true, cx),
+            Err(error) => window.push_notification((NotificationType::Error, describe_sync_target_error(error)), cx),
+        }
+    }
+
+    pub fn open_rename_dialog(&mut self, name: Arc<str>, is_file: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let input_state = cx.new(|cx| {
+            InputState::new(window, cx).default_value(name.to_string())
+        });
+
+        let page = cx.entity().downgrade();
+        let title = ts!("instance.sync.rename.title", name = name);
+        window.open_dialog(cx, move |modal, _, _| {
+            let name = name.clone();
+            let page = page.clone();
+            let input_state = input_state.clone();
+            modal
+                .title(title.clone())
+                .child(v_flex()
+                    .gap_2()
+                    .child(ts!("instance.sync.rename.description"))
+                    .child(Input::new(&input_state))
+                    .child(Button::new("rename").success().label(ts!("instance.sync.rename.save")).on_click(move |_, window, cx| {
+                        let value = input_state.read(cx).value();
+                        let value = value.as_str().trim_ascii().trim_end_matches('/');
+                        if let Err(error) = validate_sync_target(value, is_file) {
+                            window.push_notification((NotificationType::Error, describe_sync_target_error(error)), cx);
+                            return;
+                        }
+                        _ = page.update(cx, |page, cx| {
+                            page.backend_handle.send(MessageToBackend::RenameSyncTarget {
+                                old: name.clone(),
+                                new: value.into(),
+                                is_file,
+                            });
+                            page.update_sync_state(cx);
+                        });
+                        window.close_dialog(cx);
+                    })))
+        });
+    }
+
+    /// A section header followed by its entries, leaving out the entries that don't match the search and
+    /// the whole section when none of them do
+    fn create_section(&self, sync_state: &SyncState, title: SharedString, entries: &[(&str, SharedString)], is_file: bool, search: &str, cx: &mut Context<Self>) -> Vec<Div> {
+        let warning = cx.theme().red;
+        let info = cx.theme().blue;
+        let mut section = Vec::new();
+        for (name, label) in entries {
+            if matches_search(search, name, label) {
+                section.push(self.create_entry(sync_state, (*name).into(), is_file, label.clone(), warning, info, cx));
+            }
+        }
+        if !section.is_empty() {
+            section.insert(0, div().border_b_1().border_color(cx.theme().border).text_lg().child(title));
+        }
+        section
+    }
+
     pub fn create_entry(&self, sync_state: &SyncState, name: Arc<str>, is_file: bool, label: SharedString, warning: Hsla, info: Hsla, cx: &mut Context<Self>) -> Div {
         let synced_count;
         let cannot_sync_count;
         let enabled;
-        if let Some(sync_target_state) = sync_state.targets.get(&name) && sync_target_state.is_file == is_file {
+        let matching_count;
+        let excluded_count;
+        let filter;
+        let verified;
+        let modified_files;
+        let aliases;
+        let total_bytes;
+        let last_modified;
+        let pattern_matches;
+        let case_collision;
+        let last_error;
+        let cannot_sync_instances;
+        let drifted;
+        if let Some(sync_target_state) = sync_state.target(&name, is_file) {
             synced_count = sync_target_state.sync_count;
             cannot_sync_count = sync_target_state.cannot_sync_count;
             enabled = sync_target_state.enabled;
+            matching_count = sync_target_state.matching_count;
+            excluded_count = sync_target_state.excluded_count;
+            filter = sync_target_state.filter.clone();
+            verified = sync_target_state.verified;
+            modified_files = sync_target_state.modified_files.clone();
+            aliases = sync_target_state.aliases.clone();
+            total_bytes = sync_target_state.total_bytes;
+            last_modified = sync_target_state.last_modified;
+            pattern_matches = sync_target_state.pattern_matches;
+            case_collision = sync_target_state.case_collision.clone();
+            last_error = sync_target_state.last_error.clone();
+            cannot_sync_instances = sync_target_state.cannot_sync_instances.clone();
+            drifted = sync_target_state.drifted;
         } else {
             synced_count = 0;
             cannot_sync_count = 0;
             enabled = false;
+            matching_count = sync_state.total_count;
+            excluded_count = 0;
+            filter = None;
+            verified = false;
+            modified_files = Vec::new();
+            aliases = Vec::new();
+            total_bytes = 0;
+            last_modified = None;
+            pattern_matches = None;
+            case_collision = None;
+            last_error = None;
+            cannot_sync_instances = Vec::new();
+            drifted = false;
         }
-        let disabled = !enabled && cannot_sync_count > 0;
+        // Files can't be merged, so a folder in the way of a file target still prevents enabling it
+        // Two names that only differ by case would end up as the same file, so one of them has to go first
+        let disabled = !enabled && (cannot_sync_count > 0 && is_file || case_collision.is_some());
+        let needs_merge = !enabled && cannot_sync_count > 0 && !is_file && case_collision.is_none();
         let is_loading = self.loading.contains(&name);
 
-        let disable_tooltip = ts!("instance.sync.already_exists", num = cannot_sync_count, name = name);
-        let backend_handle = self.backend_handle.clone();
+        let disable_tooltip = if let Some(other) = &case_collision {
+            ts!("instance.sync.case_collision", name = other)
+        } else if needs_merge {
+            ts!("instance.sync.merge.tooltip", num = cannot_sync_count, name = name)
+        } else {
+            ts!("instance.sync.already_exists", num = cannot_sync_count, name = name)
+        };
+        // The names arrive with the detailed state, until then the tooltip only has the count
+        let disable_tooltip = if cannot_sync_instances.is_empty() || case_collision.is_some() {
+            disable_tooltip
+        } else {
+            format!("{}\n{}", disable_tooltip, ts!("instance.sync.blocking_instances", names = cannot_sync_instances.join(", "))).into()
+        };
         let checkbox = Checkbox::new(name.clone())
             .label(label)
             .disabled(disabled)
             .checked(enabled)
-            .when(disabled, |this| this.tooltip(move |window, cx| {
+            .when(disabled || needs_merge, |this| this.tooltip(move |window, cx| {
                 Tooltip::new(disable_tooltip.clone()).build(window, cx)
             }))
-            .on_click(cx.listener(move |page, value, _, cx| {
-
-            backend_handle.send(MessageToBackend::SetSyncing {
-                target: name.clone(),
-                is_file,
-                value: *value,
-            });
-
-            page.loading.insert(name.clone());
-            if page.pending.is_empty() {
-                page.pending.insert(name.clone());
-                page.update_sync_state(cx);
-            }
-        }));
+            .on_click(cx.listener({
+                let name = name.clone();
+                move |page, value, window, cx| {
+                    if !*value && !is_file {
+                        page.preview_disable(name.clone(), window, cx);
+                    } else if needs_merge {
+                        page.confirm_merge(name.clone(), cannot_sync_count, window, cx);
+                    } else {
+                        page.set_syncing(name.clone(), is_file, *value, cx);
+                    }
+                }
+            }));
 
         let mut base = h_flex().line_height(relative(1.0)).gap_2p5().child(checkbox);
 
-        if is_loading {
+        let progress = self.sync_progress.read(cx).targets.get(&name).copied();
+        if let Some((done, total)) = progress {
+            base = base.child(h_flex().gap_1().flex_shrink().text_color(info)
+                .child(div().w_24().h_1p5().rounded_full().bg(cx.theme().muted)
+                    .child(div().h_full().rounded_full().bg(info).w(relative(done as f32 / total as f32))))
+                .child(ts!("instance.sync.progress", done = done, total = total)));
+        } else if is_loading {
             base = base.child(Spinner::new());
         } else {
             if (enabled || synced_count > 0) && !is_file {
                 base = base.child(h_flex().gap_1().flex_shrink().text_color(info)
-                    .child(ts!("instance.sync.folders_count", num1 = synced_count, num2 = sync_state.total_count))
+                    .child(ts!("instance.sync.folders_count", num1 = synced_count, num2 = matching_count))
                 );
             }
+            if enabled && drifted {
+                base = base.child(h_flex().gap_1().flex_shrink().text_color(warning)
+                    .child(Icon::default().path("icons/triangle-alert.svg"))
+                    .child(ts!("instance.sync.drifted.label")));
+                base = base.child(Button::new(SharedString::from(format!("resync-{}", name))).small().label(ts!("instance.sync.drifted.resync")).on_click(cx.listener(|page, _, window, cx| {
+                    page.preview_sync(SyncDirection::Auto, window, cx);
+                })));
+            }
+            if enabled && excluded_count > 0 {
+                base = base.child(div().flex_shrink().text_color(cx.theme().muted_foreground)
+                    .child(ts!("instance.sync.excluded_count", num = excluded_count)));
+            }
+            if enabled && let Some(pattern_matches) = pattern_matches {
+                base = base.child(h_flex().gap_1().flex_shrink().text_color(info)
+                    .child(ts!("instance.sync.pattern_matches", num = pattern_matches)));
+            }
+            if enabled && let Some(last_modified) = last_modified {
+                base = base.child(div().flex_shrink().text_color(cx.theme().muted_foreground)
+                    .child(ts!("instance.sync.details.summary", size = format_bytes(total_bytes), age = format_age(last_modified))));
+            }
             if enabled && cannot_sync_count > 0 {
                 base = base.child(h_flex().gap_1().flex_shrink().text_color(warning)
                     .child(Icon::default().path("icons/triangle-alert.svg"))
-                    .child(ts!("instance.sync.unable_count", num1 = cannot_sync_count, num2 = sync_state.total_count))
+                    .child(ts!("instance.sync.unable_count", num1 = cannot_sync_count, num2 = matching_count))
+                );
+                if !is_file {
+                    let target = name.clone();
+                    base = base.child(Button::new(SharedString::from(format!("merge-{}", name))).small().label(ts!("instance.sync.merge.label")).on_click(cx.listener(move |page, _, window, cx| {
+                        page.confirm_merge(target.clone(), cannot_sync_count, window, cx);
+                    })));
+                }
+            }
+            if enabled {
+                let target = name.clone();
+                let label = match &filter {
+                    Some(filter) => ts!("instance.sync.filter.label_with_value", filter = filter),
+                    None => ts!("instance.sync.filter.label"),
+                };
+                base = base.child(Button::new(SharedString::from(format!("filter-{}", name))).small().label(label).on_click(cx.listener(move |page, _, window, cx| {
+                    page.open_filter_dialog(target.clone(), filter.clone(), window, cx);
+                })));
+            }
+            if enabled && !is_file {
+                let target = name.clone();
+                base = base.child(Checkbox::new(SharedString::from(format!("verify-{}", name)))
+                    .label(ts!("instance.sync.verify.label"))
+                    .checked(verified)
+                    .on_click(cx.listener(move |page, value: &bool, _, cx| {
+                        page.backend_handle.send(MessageToBackend::SetSyncTargetVerified {
+                            target: target.clone(),
+                            value: *value,
+                        });
+                        page.update_sync_state(cx);
+                    })));
+            }
+            if enabled && let Some(error) = last_error {
+                let error = SharedString::from(error);
+                base = base.child(h_flex().id(SharedString::from(format!("error-{}", name))).gap_1().flex_shrink().text_color(cx.theme().red)
+                    .child(Icon::default().path("icons/triangle-alert.svg"))
+                    .child(ts!("instance.sync.last_error"))
+                    .tooltip(move |window, cx| Tooltip::new(error.clone()).build(window, cx)));
+            }
+            if enabled && let Some(other) = &case_collision {
+                base = base.child(h_flex().gap_1().flex_shrink().text_color(warning)
+                    .child(Icon::default().path("icons/triangle-alert.svg"))
+                    .child(ts!("instance.sync.case_collision", name = other))
+                );
+            }
+            if !aliases.is_empty() {
+                base = base.child(h_flex().gap_1().flex_shrink().text_color(warning)
+                    .child(Icon::default().path("icons/triangle-alert.svg"))
+                    .child(ts!("instance.sync.same_folder_as", names = aliases.join(", ")))
                 );
             }
+            if !modified_files.is_empty() {
+                let tooltip: SharedString = modified_files.iter().map(|file| &**file).collect::<Vec<_>>().join("\n").into();
+                let target = name.clone();
+                base = base
+                    .child(h_flex().id(SharedString::from(format!("modified-{}", name))).gap_1().flex_shrink().text_color(warning)
+                        .child(Icon::default().path("icons/triangle-alert.svg"))
+                        .child(ts!("instance.sync.verify.modified_count", num = modified_files.len()))
+                        .tooltip(move |window, cx| Tooltip::new(tooltip.clone()).build(window, cx)))
+                    .child(Button::new(SharedString::from(format!("accept-{}", name))).small().label(ts!("instance.sync.verify.accept")).on_click(cx.listener(move |page, _, _, cx| {
+                        page.backend_handle.send(MessageToBackend::SetSyncTargetVerified {
+                            target: target.clone(),
+                            value: true,
+                        });
+                        page.update_sync_state(cx);
+                    })));
+            }
         }
 
 
@@ -136,104 +974,364 @@ impl Render for SyncingPage {
 
         let sync_folder = sync_state.sync_folder.clone();
 
+        let search = self.search_input_state.read(cx).value().trim().to_lowercase();
+
+        let files = [
+            ("options.txt", ts!("instance.sync.targets.options")),
+            ("servers.dat", ts!("instance.sync.targets.servers")),
+            ("command_history.txt", ts!("instance.sync.targets.commands")),
+            ("hotbar.nbt", ts!("instance.sync.targets.hotbars")),
+        ];
+        let folders = [
+            ("saves", ts!("instance.sync.targets.saves")),
+            ("config", ts!("instance.sync.targets.config")),
+            ("screenshots", ts!("instance.sync.targets.screenshots")),
+            ("resourcepacks", ts!("instance.sync.targets.resourcepacks")),
+            ("shaderpacks", ts!("instance.sync.targets.shaderpacks")),
+        ];
+        let mods = [
+            ("flashback", ts!("instance.sync.targets.flashback")),
+            ("Distant_Horizons_server_data", ts!("instance.sync.targets.dh")),
+            (".voxy", ts!("instance.sync.targets.voxy")),
+            ("xaero", ts!("instance.sync.targets.xaero")),
+            (".bobby", ts!("instance.sync.targets.bobby")),
+            ("schematics", ts!("instance.sync.targets.litematic")),
+        ];
+
+        // Custom files come before custom folders like the named sections, each alphabetical regardless of case
+        let mut custom_targets: Vec<_> = sync_state.targets.iter()
+            .filter(|(name, state)| state.enabled && !NAMED_SYNC_TARGETS.contains(&(&***name, state.is_file)))
+            .map(|(name, state)| {
+                let label = if state.is_file {
+                    ts!("instance.sync.sync_name_file", name = name)
+                } else {
+                    ts!("instance.sync.sync_name_folder", name = name)
+                };
+                (name, state, label)
+            })
+            .filter(|(name, _, label)| matches_search(&search, name, label))
+            .collect();
+        custom_targets.sort_by(|(a_name, a, _), (b_name, b, _)| {
+            b.is_file.cmp(&a.is_file).then_with(|| a_name.to_lowercase().cmp(&b_name.to_lowercase()))
+        });
+
         let warning = cx.theme().red;
         let info = cx.theme().blue;
         let content = v_flex().size_full().p_3().gap_3()
             .child(ts!("instance.sync.description"))
-            .child(Button::new("open").info().icon(IconName::FolderOpen).label(ts!("instance.sync.open_folder")).on_click(move |_, window, cx| {
-                crate::open_folder(&sync_folder, window, cx);
-            }).w_72())
-            .child(div().border_b_1().border_color(cx.theme().border).text_lg().child(ts!("instance.sync.files")))
-            .child(self.create_entry(sync_state, "options.txt".into(), true,  ts!("instance.sync.targets.options"), warning, info, cx))
-            .child(self.create_entry(sync_state, "servers.dat".into(), true, ts!("instance.sync.targets.servers"), warning, info, cx))
-            .child(self.create_entry(sync_state, "command_history.txt".into(), true, ts!("instance.sync.targets.commands"), warning, info, cx))
-            .child(self.create_entry(sync_state, "hotbar.nbt".into(), true, ts!("instance.sync.targets.hotbars"), warning, info, cx))
-            .child(div().border_b_1().border_color(cx.theme().border).text_lg().child(ts!("instance.sync.folders")))
-            .child(self.create_entry(sync_state, "saves".into(), false, ts!("instance.sync.targets.saves"), warning, info, cx))
-            .child(self.create_entry(sync_state, "config".into(), false, ts!("instance.sync.targets.config"), warning, info, cx))
-            .child(self.create_entry(sync_state, "screenshots".into(), false, ts!("instance.sync.targets.screenshots"), warning, info, cx))
-            .child(self.create_entry(sync_state, "resourcepacks".into(), false, ts!("instance.sync.targets.resourcepacks"), warning, info, cx))
-            .child(self.create_entry(sync_state, "shaderpacks".into(), false, ts!("instance.sync.targets.shaderpacks"), warning, info, cx))
-            .child(div().border_b_1().border_color(cx.theme().border).text_lg().child(ts!("instance.sync.mods")))
-            .child(self.create_entry(sync_state, "flashback".into(), false, ts!("instance.sync.targets.flashback"), warning, info, cx))
-            .child(self.create_entry(sync_state, "Distant_Horizons_server_data".into(), false, ts!("instance.sync.targets.dh"), warning, info, cx))
-            .child(self.create_entry(sync_state, ".voxy".into(), false, ts!("instance.sync.targets.voxy"), warning, info, cx))
-            .child(self.create_entry(sync_state, "xaero".into(), false, ts!("instance.sync.targets.xaero"), warning, info, cx))
-            .child(self.create_entry(sync_state, ".bobby".into(), false, ts!("instance.sync.targets.bobby"), warning, info, cx))
-            .child(self.create_entry(sync_state, "schematics".into(), false, ts!("instance.sync.targets.litematic"), warning, info, cx))
-            .child(div().border_b_1().border_color(cx.theme().border).text_lg().child(ts!("instance.sync.custom")))
-            .children(sync_state.targets.iter().filter_map(|(name, state)| {
-                if !state.enabled || NAMED_SYNC_TARGETS.contains(&**name) {
-                    return None;
-                }
-                let label = if state.is_file {
-                    ts!("instance.sync.sync_name_file", name = name)
+            .child(Input::new(&self.search_input_state).max_w_128())
+            .child(h_flex()
+                .gap_3()
+                .child(Checkbox::new("pause")
+                    .label(ts!("instance.sync.pause.label"))
+                    .checked(sync_state.paused)
+                    .on_click(cx.listener(|page, value: &bool, _, cx| {
+                        page.backend_handle.send(MessageToBackend::SetSyncingPaused {
+                            value: *value,
+                        });
+                        page.update_sync_state(cx);
+                    })))
+                .child(Checkbox::new("watch")
+                    .label(ts!("instance.sync.watch.label"))
+                    .tooltip(|window, cx| Tooltip::new(ts!("instance.sync.watch.tooltip")).build(window, cx))
+                    .checked(sync_state.watch_sync)
+                    .on_click(cx.listener(|page, value: &bool, _, cx| {
+                        page.backend_handle.send(MessageToBackend::SetWatchSync {
+                            value: *value,
+                        });
+                        page.update_sync_state(cx);
+                    })))
+                .child(Checkbox::new("copy_fallback")
+                    .label(ts!("instance.sync.copy_fallback.label"))
+                    .tooltip(|window, cx| Tooltip::new(ts!("instance.sync.copy_fallback.tooltip")).build(window, cx))
+                    .checked(sync_state.link_copy_fallback)
+                    .on_click(cx.listener(|page, value: &bool, _, cx| {
+                        page.backend_handle.send(MessageToBackend::SetLinkCopyFallback {
+                            value: *value,
+                        });
+                        page.update_sync_state(cx);
+                    })))
+                .when(sync_state.paused, |this| {
+                    this.child(h_flex().gap_1().text_color(warning)
+                        .child(Icon::default().path("icons/triangle-alert.svg"))
+                        .child(ts!("instance.sync.pause.active")))
+                })
+                .when_some(sync_state.location_error.clone(), |this, error| {
+                    this.child(h_flex().gap_1().text_color(warning)
+                        .child(Icon::default().path("icons/triangle-alert.svg"))
+                        .child(SharedString::from(error)))
+                }))
+            .child(h_flex()
+                .gap_2()
+                .child(Button::new("open").info().icon(IconName::FolderOpen).label(ts!("instance.sync.open_folder")).on_click(move |_, window, cx| {
+                    crate::open_folder(&sync_folder, window, cx);
+                }).w_72())
+                .child(Button::new("rescan").label(ts!("instance.sync.rescan.label")).tooltip(ts!("instance.sync.rescan.tooltip")).loading(self.rescanning).disabled(self.rescanning).on_click(cx.listener(|page, _, _, cx| {
+                    page.rescan_instances(cx);
+                })))
+                .child(Button::new("maintain").label(ts!("instance.sync.maintain.label")).loading(self.maintaining).disabled(self.maintaining).on_click(cx.listener(|page, _, window, cx| {
+                    page.maintain_store(window, cx);
+                })))
+                .child(Button::new("sync_log").label(ts!("instance.sync.log.label")).on_click(cx.listener(|page, _, window, cx| {
+                    page.show_sync_log(window, cx);
+                })))
+                .child(Button::new("apply_auto").label(ts!("instance.sync.apply.auto")).tooltip(ts!("instance.sync.apply.auto_description")).on_click(cx.listener(|page, _, window, cx| {
+                    page.preview_sync(SyncDirection::Auto, window, cx);
+                })))
+                .child(Button::new("apply_push").label(ts!("instance.sync.apply.push")).tooltip(ts!("instance.sync.apply.push_description")).on_click(cx.listener(|page, _, window, cx| {
+                    page.preview_sync(SyncDirection::Push, window, cx);
+                })))
+                .child(Button::new("apply_pull").label(ts!("instance.sync.apply.pull")).tooltip(ts!("instance.sync.apply.pull_description")).on_click(cx.listener(|page, _, window, cx| {
+                    page.preview_sync(SyncDirection::Pull, window, cx);
+                })))
+                .child(Button::new("export_config").label(ts!("instance.sync.transfer.export")).on_click(cx.listener(|page, _, window, cx| {
+                    page.export_sync_config(window, cx);
+                })))
+                .child(Button::new("import_config").label(ts!("instance.sync.transfer.import")).on_click(cx.listener(|page, _, window, cx| {
+                    page.open_import_dialog(window, cx);
+                })))
+                .child(Button::new("copy_summary").icon(IconName::Copy).label(ts!("instance.sync.copy_summary.label")).on_click(cx.listener(|page, _, window, cx| {
+                    page.copy_sync_config_summary(window, cx);
+                }))))
+            .when_some(sync_state.interrupted_operation.as_ref(), |content, interrupted| {
+                let message = if interrupted.enabling {
+                    ts!("instance.sync.interrupted.enabling", name = interrupted.target, count = interrupted.completed_count)
                 } else {
-                    ts!("instance.sync.sync_name_folder", name = name)
+                    ts!("instance.sync.interrupted.disabling", name = interrupted.target, count = interrupted.completed_count)
                 };
-                Some(self.create_entry(sync_state, name.clone(), state.is_file, label, warning, info, cx))
+                content.child(h_flex()
+                    .gap_2()
+                    .p_2()
+                    .border_1()
+                    .rounded(cx.theme().radius)
+                    .border_color(cx.theme().warning)
+                    .child(div().flex_1().child(message))
+                    .child(Button::new("resume").success().label(ts!("instance.sync.interrupted.resume")).on_click(cx.listener(|page, _, _, cx| {
+                        page.backend_handle.send(MessageToBackend::ResumeSyncOperation);
+                        page.update_sync_state(cx);
+                    })))
+                    .child(Button::new("discard").label(ts!("instance.sync.interrupted.discard")).on_click(cx.listener(|page, _, _, cx| {
+                        page.backend_handle.send(MessageToBackend::DiscardSyncOperation);
+                        page.update_sync_state(cx);
+                    }))))
+            })
+            .children(self.create_section(sync_state, ts!("instance.sync.files"), &files, true, &search, cx))
+            .children(self.create_section(sync_state, ts!("instance.sync.folders"), &folders, false, &search, cx))
+            .children(self.create_section(sync_state, ts!("instance.sync.mods"), &mods, false, &search, cx))
+            .when(!custom_targets.is_empty(), |this| {
+                this.child(div().border_b_1().border_color(cx.theme().border).text_lg().child(ts!("instance.sync.custom")))
+            })
+            .children(custom_targets.into_iter().map(|(name, state, label)| {
+                let is_file = state.is_file;
+                let target = name.clone();
+                let icon = if is_file { IconName::File } else { IconName::Folder };
+                let entry = self.create_entry(sync_state, name.clone(), is_file, label, warning, info, cx);
+                let entry = entry.when(!self.loading.contains(name), |entry| {
+                    let renamed = target.clone();
+                    entry.child(Button::new(SharedString::from(format!("rename-{}", name)))
+                        .small()
+                        .icon(Icon::default().path("icons/pencil.svg"))
+                        .tooltip(ts!("instance.sync.rename.tooltip"))
+                        .on_click(cx.listener(move |page, _, window, cx| {
+                            page.open_rename_dialog(renamed.clone(), is_file, window, cx);
+                        })))
+                    .child(Button::new(SharedString::from(format!("remove-{}", name)))
+                        .small()
+                        .danger()
+                        .icon(Icon::default().path("icons/trash-2.svg"))
+                        .tooltip(ts!("instance.sync.remove_custom"))
+                        .on_click(cx.listener(move |page, _, window, cx| {
+                            // Folders go through the same confirmation as unchecking, which lists the links that will be removed
+                            if is_file {
+                                page.set_syncing(target.clone(), true, false, cx);
+                            } else {
+                                page.preview_disable(target.clone(), window, cx);
+                            }
+                        })))
+                });
+                h_flex().gap_2().child(Icon::new(icon).small().text_color(cx.theme().muted_foreground)).child(entry)
             }))
             .child(h_flex()
                 .w_full()
                 .max_w_128()
                 .gap_2()
                 .child(Input::new(&self.custom_input_state).w_full())
-                .child(Button::new("custom_file").label(ts!("instance.sync.sync_file")).on_click(cx.listener(|page, _, _, cx| {
-                    let input = page.custom_input_state.read(cx).value();
-                    let input = input.as_str().trim_ascii();
-                    if SafePath::new(input).is_some() {
-                        let name: Arc<str> = input.into();
-                        page.backend_handle.send(MessageToBackend::SetSyncing {
-                            target: name.clone(),
-                            is_file: true,
-                            value: true,
-                        });
-
-                        page.loading.insert(name.clone());
-                        if page.pending.is_empty() {
-                            page.pending.insert(name.clone());
-                            page.update_sync_state(cx);
-                        }
-                    }
+                .child(Button::new("custom_file").label(ts!("instance.sync.sync_file")).on_click(cx.listener(|page, _, window, cx| {
+                    page.add_custom_target(true, window, cx);
                 })))
-                .child(Button::new("custom_folder").label(ts!("instance.sync.sync_folder")).on_click(cx.listener(|page, _, _, cx| {
-                    let input = page.custom_input_state.read(cx).value();
-                    let input = input.as_str().trim_ascii();
-                    if SafePath::new(input).is_some() {
-                        let name: Arc<str> = input.into();
-                        page.backend_handle.send(MessageToBackend::SetSyncing {
-                            target: name.clone(),
-                            is_file: false,
-                            value: true,
-                        });
+                .child(Button::new("custom_folder").label(ts!("instance.sync.sync_folder")).on_click(cx.listener(|page, _, window, cx| {
+                    page.add_custom_target(false, window, cx);
+                }))))
+            .child(div().border_b_1().border_color(cx.theme().border).text_lg().child(ts!("instance.sync.instance_settings.title")))
+            .child(self.render_instance_settings(sync_state, cx))
+            .child(div().border_b_1().border_color(cx.theme().border).text_lg().child(ts!("instance.sync.overrides.title")))
+            .child(self.render_instance_overrides(sync_state, cx));
 
-                        page.loading.insert(name.clone());
-                        if page.pending.is_empty() {
-                            page.pending.insert(name.clone());
-                            page.update_sync_state(cx);
-                        }
-                    }
+        let enabled_count = sync_state.targets.values().filter(|state| state.enabled).count();
+        let recommended_enabled = RECOMMENDED_SYNC_TARGETS.iter()
+            .all(|(name, is_file)| sync_state.target(name, *is_file).is_some_and(|state| state.enabled));
+        let title = h_flex()
+            .gap_8()
+            .child(ts!("instance.sync.label"))
+            .child(h_flex()
+                .gap_3()
+                .child(div().text_sm().text_color(cx.theme().muted_foreground).child(ts!("instance.sync.enabled_count", num = enabled_count)))
+                .child(Button::new("enable_recommended").small().success().label(ts!("instance.sync.recommended.label"))
+                    .tooltip(ts!("instance.sync.recommended.tooltip"))
+                    .loading(!self.batched.is_empty())
+                    .disabled(recommended_enabled || !self.batched.is_empty())
+                    .on_click(cx.listener(|page, _, _, cx| {
+                        page.enable_recommended(cx);
+                    })))
+                .child(Button::new("disable_all").small().danger().label(ts!("instance.sync.disable_all.label")).disabled(enabled_count == 0 || !self.batched.is_empty()).on_click(cx.listener(|page, _, window, cx| {
+                    page.confirm_disable_all(window, cx);
                 }))));
 
-        ui::page(cx, h_flex().gap_8().child(ts!("instance.sync.label"))).child(content).overflow_y_scrollbar()
+        ui::page(cx, title).child(content).overflow_y_scrollbar()
     }
 }
 
-static NAMED_SYNC_TARGETS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
+fn open_maintenance_report(report: StoreMaintenanceReport, window: &mut Window, cx: &mut App) {
+    let summary = ts!("instance.sync.maintain.summary", links = report.verified_links, size = format_bytes(report.reclaimed_bytes));
+    let actions: Vec<SharedString> = report.actions.into_iter().map(SharedString::from).collect();
+    let problems: Vec<SharedString> = report.problems.into_iter().map(SharedString::from).collect();
+
+    window.open_dialog(cx, move |modal, _, cx| {
+        let content = v_flex()
+            .gap_2()
+            .child(summary.clone())
+            .when(actions.is_empty(), |content| content.child(ts!("instance.sync.maintain.nothing_done")))
+            .children(actions.iter().cloned())
+            .when(!problems.is_empty(), |content| {
+                content
+                    .child(div().border_b_1().border_color(cx.theme().border).child(ts!("instance.sync.maintain.problems")))
+                    .children(problems.iter().map(|problem| div().text_color(cx.theme().red).child(problem.clone())))
+            });
+
+        modal.title(ts!("instance.sync.maintain.title")).child(content)
+    });
+}
+
+/// How many of the most recent sync log entries are shown
+const SYNC_LOG_LIMIT: usize = 200;
+
+fn open_sync_log(entries: Vec<SyncLogEntry>, window: &mut Window, cx: &mut App) {
+    window.open_dialog(cx, move |modal, _, cx| {
+        let rows = entries.iter().map(|entry| {
+            let target = &entry.target;
+            let description = match entry.operation {
+                SyncLogOperation::CopyFile => ts!("instance.sync.log.copy_file", target = target),
+                SyncLogOperation::CreateLink => ts!("instance.sync.log.create_link", target = target),
+                SyncLogOperation::RemoveLink => ts!("instance.sync.log.remove_link", target = target),
+                SyncLogOperation::RepairLink => ts!("instance.sync.log.repair_link", target = target),
+                SyncLogOperation::ChangeOption => ts!("instance.sync.log.change_option", target = target),
+                SyncLogOperation::Failed => ts!("instance.sync.log.failed", target = target, error = entry.error.as_deref().unwrap_or_default()),
+            };
+            h_flex()
+                .gap_2()
+                .when(entry.operation == SyncLogOperation::Failed, |row| row.text_color(cx.theme().red))
+                .child(div().text_color(cx.theme().muted_foreground).child(format_age(entry.time)))
+                .child(SharedString::from(entry.instance.clone()))
+                .child(description)
+        });
+
+        let content = v_flex()
+            .gap_1()
+            .max_h_128()
+            .when(entries.is_empty(), |content| content.child(ts!("instance.sync.log.empty")))
+            .children(rows)
+            .overflow_y_scrollbar();
+
+        modal.title(ts!("instance.sync.log.title")).child(content)
+    });
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes < 1000 {
+        format!("{} bytes", bytes)
+    } else if bytes < 1000*1000 {
+        format!("{}kB", bytes/1000)
+    } else if bytes < 1000*1000*1000 {
+        format!("{}MB", bytes/1000/1000)
+    } else {
+        format!("{}GB", bytes/1000/1000/1000)
+    }
+}
+
+fn format_age(time: SystemTime) -> SharedString {
+    let seconds = SystemTime::now().duration_since(time).unwrap_or_default().as_secs();
+    if seconds < 60 {
+        ts!("instance.sync.details.just_now")
+    } else if seconds < 60*60 {
+        ts!("instance.sync.details.minutes_ago", num = seconds/60)
+    } else if seconds < 60*60*24 {
+        ts!("instance.sync.details.hours_ago", num = seconds/60/60)
+    } else {
+        ts!("instance.sync.details.days_ago", num = seconds/60/60/24)
+    }
+}
+
+fn describe_sync_target_error(error: SyncTargetError) -> SharedString {
+    match error {
+        SyncTargetError::Empty => ts!("instance.sync.invalid.empty"),
+        SyncTargetError::EmptySegment => ts!("instance.sync.invalid.empty_segment"),
+        SyncTargetError::ParentDir => ts!("instance.sync.invalid.parent_dir"),
+        SyncTargetError::ReservedName(name) => ts!("instance.sync.invalid.reserved", name = name),
+        SyncTargetError::TrailingDotOrSpace(name) => ts!("instance.sync.invalid.trailing", name = name),
+        SyncTargetError::InvalidCharacter(name) => ts!("instance.sync.invalid.character", name = name),
+        SyncTargetError::TooLong => ts!("instance.sync.invalid.too_long", num = MAX_SYNC_TARGET_LENGTH),
+        SyncTargetError::FolderPattern => ts!("instance.sync.invalid.folder_pattern"),
+    }
+}
+
+fn matches_search(search: &str, name: &str, label: &str) -> bool {
+    search.is_empty() || name.to_lowercase().contains(search) || label.to_lowercase().contains(search)
+}
+
+/// What the recommended button enables, the Files and Folders sections
+const RECOMMENDED_SYNC_TARGETS: &[(&str, bool)] = &[
+    ("options.txt", true),
+    ("servers.dat", true),
+    ("command_history.txt", true),
+    ("hotbar.nbt", true),
+    ("saves", false),
+    ("config", false),
+    ("screenshots", false),
+    ("resourcepacks", false),
+    ("shaderpacks", false),
+];
+
+/// The targets with their own row in the named sections, as name and whether it's a file
+static NAMED_SYNC_TARGETS: Lazy<HashSet<(&'static str, bool)>> = Lazy::new(|| {
     HashSet::from([
-        "options.txt",
-        "servers.dat",
-        "command_history.txt",
-        "hotbar.nbt",
-        "saves",
-        "config",
-        "screenshots",
-        "shaderpacks",
-        "flashback",
-        "Distant_Horizons_server_data",
-        ".voxy",
-        "xaero",
-        ".bobby",
-        "schematics"
+        ("options.txt", true),
+        ("servers.dat", true),
+        ("command_history.txt", true),
+        ("hotbar.nbt", true),
+        ("saves", false),
+        ("config", false),
+        ("screenshots", false),
+        ("resourcepacks", false),
+        ("shaderpacks", false),
+        ("flashback", false),
+        ("Distant_Horizons_server_data", false),
+        (".voxy", false),
+        ("xaero", false),
+        (".bobby", false),
+        ("schematics", false),
     ])
 });
+
+fn describe_sync_operation(operation: &SyncOperation) -> SharedString {
+    match operation {
+        SyncOperation::RemoveLink { name, .. } => ts!("instance.sync.preview_sync.remove_link", name = name),
+        SyncOperation::CreateLink { name, .. } => ts!("instance.sync.preview_sync.create_link", name = name),
+        SyncOperation::RepairLink { name, .. } => ts!("instance.sync.preview_sync.repair_link", name = name),
+        SyncOperation::RefreshFolder { name, .. } => ts!("instance.sync.preview_sync.refresh_folder", name = name),
+        SyncOperation::CopyFile { name, to_store: false, .. } => ts!("instance.sync.preview_sync.copy_file", name = name),
+        SyncOperation::CopyFile { name, to_store: true, .. } => ts!("instance.sync.preview_sync.copy_to_store", name = name),
+        SyncOperation::WriteOptions { changed_keys, .. } => ts!("instance.sync.preview_sync.write_options", count = changed_keys.len()),
+        SyncOperation::WriteServers { .. } => ts!("instance.sync.preview_sync.write_servers"),
+        SyncOperation::WriteHotbars { .. } => ts!("instance.sync.preview_sync.write_hotbars"),
+    }
+}
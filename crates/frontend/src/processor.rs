@@ -4,7 +4,7 @@ use bridge::{instance::InstanceStatus, message::{BridgeNotificationType, Message
 use gpui::{AnyWindowHandle, App, AppContext, Entity, SharedString, TitlebarOptions, Window, WindowDecorations, WindowHandle, WindowOptions, px, size};
 use gpui_component::{notification::{Notification, NotificationType}, Root, WindowExt};
 
-use crate::{entity::{DataEntities, account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata}, game_output::{GameOutput, GameOutputRoot}, interface_config::InterfaceConfig, ts};
+use crate::{entity::{DataEntities, account::AccountEntries, instance::InstanceEntries, metadata::FrontendMetadata, sync_progress::SyncProgressEntries}, game_output::{GameOutput, GameOutputRoot}, interface_config::InterfaceConfig, ts};
 
 pub struct Processor {
     data: DataEntities,
@@ -159,6 +159,9 @@ impl Processor {
                     window.refresh();
                 });
             },
+            MessageToFrontend::SyncProgress { target, done, total } => {
+                SyncProgressEntries::set(&self.data.sync_progress, target, done, total, cx);
+            },
             MessageToFrontend::CloseModal => {
                 let Some(handle) = self.main_window_handle else {
                     return;
@@ -208,7 +208,7 @@ impl<'a> CompoundRef<'a> {
         nbt
     }
 
-    fn clone_into(&self, mut into: CompoundRefMut<'_>) {
+    pub fn clone_into(&self, mut into: CompoundRefMut<'_>) {
         for (key, entry) in self.entries() {
             match entry {
                 NBTRef::Byte(v) => into.insert_byte(key, *v),
@@ -464,6 +464,24 @@ impl<'a> CompoundRefMut<'a> {
         }
     }
 
+    /// Inserts a deep copy of `value`, which may belong to a different NBT
+    pub fn insert_copy(&mut self, key: &str, value: NBTRef<'_>) {
+        match value {
+            NBTRef::Byte(v) => self.insert_byte(key, *v),
+            NBTRef::Short(v) => self.insert_short(key, *v),
+            NBTRef::Int(v) => self.insert_int(key, *v),
+            NBTRef::Long(v) => self.insert_long(key, *v),
+            NBTRef::Float(v) => self.insert_float(key, *v),
+            NBTRef::Double(v) => self.insert_double(key, *v),
+            NBTRef::ByteArray(v) => self.insert_byte_array(key, v.clone()),
+            NBTRef::String(v) => self.insert_string(key, v.clone()),
+            NBTRef::List(v) => v.clone_into(self.create_list(key, v.children_type)),
+            NBTRef::Compound(v) => v.clone_into(self.create_compound(key)),
+            NBTRef::IntArray(v) => self.insert_int_array(key, v.clone()),
+            NBTRef::LongArray(v) => self.insert_long_array(key, v.clone()),
+        }
+    }
+
     pub fn find_list(&self, key: &str, type_id: TagType) -> Option<ListRef<'_>> {
         let idx = self.find_idx(key)?;
         match self.get_node(idx) {
@@ -588,7 +606,7 @@ impl PartialEq for ListRef<'_> {
 }
 
 impl<'a> ListRef<'a> {
-    fn clone_into(&self, mut into: ListRefMut<'_>) {
+    pub fn clone_into(&self, mut into: ListRefMut<'_>) {
         for child in self.iter() {
             match child {
                 NBTRef::Byte(v) => into.insert_byte(*v),
@@ -7,9 +7,9 @@ use std::sync::Arc;
 use std::fmt::Write;
 use std::time::SystemTime;
 
-use bridge::message::MessageToFrontend;
+use bridge::message::{MessageToBackend, MessageToFrontend, SyncChangeReport, SyncDirection, SyncOperation};
 use bridge::modal_action::{ModalAction, ProgressTrackerFinishType};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use fern::colors::ColoredLevelConfig;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use native_dialog::DialogBuilder;
@@ -21,6 +21,30 @@ struct Args {
     /// Instance to launch, instead of opening the launcher
     #[arg(long)]
     run_instance: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage synced files and folders without opening the launcher
+    Sync {
+        #[command(subcommand)]
+        command: SyncCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum SyncCommand {
+    /// Apply syncing to every instance the same way launching them does
+    Apply {
+        /// Only apply syncing to the instance with this name
+        #[arg(long)]
+        instance: Option<String>,
+        /// Print what would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 pub mod panic;
@@ -53,7 +77,10 @@ fn main() {
 
     panic::install_logging_hook();
 
-    if let Some(run_instance) = args.run_instance {
+    if let Some(Command::Sync { command: SyncCommand::Apply { instance, dry_run } }) = args.command {
+        let success = run_sync_apply(launcher_dir, instance, dry_run);
+        std::process::exit(if success { 0 } else { 1 });
+    } else if let Some(run_instance) = args.run_instance {
         let (backend_recv, backend_handle, mut frontend_recv, frontend_handle) = bridge::handle::create_pair();
 
         backend::start(launcher_dir.clone(), frontend_handle, backend_handle.clone(), backend_recv);
@@ -82,6 +109,108 @@ fn main() {
     }
 }
 
+/// Returns false if the instance wasn't found or any part of syncing failed
+fn run_sync_apply(launcher_dir: PathBuf, instance: Option<String>, dry_run: bool) -> bool {
+    let (backend_recv, backend_handle, mut frontend_recv, frontend_handle) = bridge::handle::create_pair();
+
+    backend::start(launcher_dir, frontend_handle, backend_handle.clone(), backend_recv);
+
+    let mut id = None;
+    if let Some(instance) = &instance {
+        while let Some(message) = frontend_recv.try_recv() {
+            if let MessageToFrontend::InstanceAdded { id: added, name, .. } = message && name.as_str() == instance.as_str() {
+                id = Some(added);
+                break;
+            }
+        }
+        if id.is_none() {
+            eprintln!("Unable to find instance {}", instance);
+            return false;
+        }
+    }
+
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::GetBackendConfiguration { channel: send });
+    if recv.blocking_recv().is_ok_and(|config| config.syncing_paused) {
+        eprintln!("Syncing is paused, turn it back on in the launcher to apply it");
+        return false;
+    }
+
+    if dry_run {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        backend_handle.send(MessageToBackend::PreviewSync { direction: SyncDirection::Auto, channel: send });
+        let Ok(plans) = recv.blocking_recv() else {
+            eprintln!("The backend stopped before previewing syncing");
+            return false;
+        };
+
+        let plans: Vec<_> = plans.into_iter().filter(|(name, _)| instance.as_deref().is_none_or(|instance| &**name == instance)).collect();
+        if plans.is_empty() {
+            println!("Nothing to sync");
+        }
+        for (name, plan) in plans {
+            println!("{}:", name);
+            for operation in &plan.operations {
+                println!("  would {}", describe_sync_operation(operation));
+            }
+            for folder in &plan.blocked_folders {
+                println!("  can't link {}, the instance already has its own folder", folder);
+            }
+        }
+        return true;
+    }
+
+    let (send, recv) = tokio::sync::oneshot::channel();
+    backend_handle.send(MessageToBackend::ApplySyncingWithReport { id, channel: send });
+    let Ok(reports) = recv.blocking_recv() else {
+        eprintln!("The backend stopped before syncing finished");
+        return false;
+    };
+
+    if reports.is_empty() {
+        println!("Nothing to sync");
+    }
+    let mut success = true;
+    for (name, report) in reports {
+        println!("{}:", name);
+        print_sync_report(&report);
+        success &= report.errors.is_empty();
+    }
+    success
+}
+
+fn describe_sync_operation(operation: &SyncOperation) -> String {
+    match operation {
+        SyncOperation::RemoveLink { name, .. } => format!("unlink {}", name),
+        SyncOperation::CreateLink { name, .. } => format!("link {}", name),
+        SyncOperation::RepairLink { name, .. } => format!("recreate the missing synced folder for {}", name),
+        SyncOperation::RefreshFolder { name, .. } => format!("bring the copy of {} up to date", name),
+        SyncOperation::CopyFile { name, to_store: false, .. } => format!("copy {}", name),
+        SyncOperation::CopyFile { name, to_store: true, .. } => format!("copy {} into the synced folder", name),
+        SyncOperation::WriteOptions { changed_keys, .. } => format!("change {} option(s) in options.txt", changed_keys.len()),
+        SyncOperation::WriteServers { .. } => "update servers.dat".to_string(),
+        SyncOperation::WriteHotbars { .. } => "update hotbar.nbt".to_string(),
+    }
+}
+
+fn print_sync_report(report: &SyncChangeReport) {
+    let changes = [
+        ("copied", &report.files_copied),
+        ("linked", &report.links_created),
+        ("unlinked", &report.links_removed),
+        ("repaired", &report.links_repaired),
+        ("changed options", &report.options_changed),
+    ];
+    for (label, names) in changes {
+        if !names.is_empty() {
+            println!("  {}: {}", label, names.join(", "));
+        }
+    }
+    for error in &report.errors {
+        println!("  failed {}: {}", error.target, error.message);
+    }
+}
+
 fn show_error(error: String) {
     log::error!("{}", error);
     _ = DialogBuilder::message()
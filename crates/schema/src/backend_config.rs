@@ -1,14 +1,127 @@
-use std::{collections::BTreeSet, sync::Arc};
+use std::{collections::{BTreeMap, BTreeSet}, sync::Arc};
 
 use enumset::{EnumSet, EnumSetType};
 use serde::{Deserialize, Serialize};
 
+use crate::instance::{
+    InstanceConfiguration, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration, InstanceLinuxWrapperConfiguration,
+    InstanceSystemLibrariesConfiguration, InstanceWrapperCommandConfiguration,
+};
+
+/// The resource packs depend on what each instance has installed, so they aren't synced unless configured otherwise
+pub const DEFAULT_PRESERVED_OPTION_KEYS: &[&str] = &["resourcePacks", "incompatibleResourcePacks"];
+
+/// Files the OS creates in folders on its own, these are never copied into the synced folder
+pub const DEFAULT_SYNC_IGNORE_PATTERNS: &[&str] = &[".DS_Store", "._*", "Thumbs.db", "desktop.ini"];
+
+/// Bumped whenever an older config.json needs to be changed to load correctly, see migrate_backend_config
+pub const BACKEND_CONFIG_VERSION: u32 = 1;
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct BackendConfig {
+    /// The BACKEND_CONFIG_VERSION this config was written with, missing in configs from before versioning
+    #[serde(default)]
+    pub version: u32,
     #[serde(default, skip_serializing_if = "is_default_sync_targets", deserialize_with = "try_deserialize_sync_targets")]
     pub sync_targets: SyncTargets,
     #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
     pub dont_open_game_output_when_launching: bool,
+    #[serde(default, skip_serializing_if = "EnumSet::is_empty", deserialize_with = "crate::try_deserialize")]
+    pub synced_instance_settings: EnumSet<SyncedInstanceSetting>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty", deserialize_with = "crate::try_deserialize")]
+    pub verified_sync_targets: BTreeSet<Arc<str>>,
+    /// Keyed by target name, which is only ever synced as a file or a folder, see reconcile_sync_targets
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty", deserialize_with = "crate::try_deserialize")]
+    pub sync_target_filters: BTreeMap<Arc<str>, Arc<str>>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub link_strategy: LinkStrategy,
+    /// Copies synced folders into instances that the launcher isn't allowed to link them into. The copies only
+    /// pick up changes when syncing runs, so this is off unless the user asks for it
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub link_copy_fallback: bool,
+    /// Stops syncing from being applied to any instance without forgetting the configured targets
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub syncing_paused: bool,
+    /// Re-syncs file targets when they are changed outside of a launch, e.g. by a game that is still running
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub watch_sync: bool,
+    /// options.txt keys that keep each instance's own value instead of being synced, `*` matches any characters
+    /// e.g. `key_key.*`. Empty means the resource pack lists, see DEFAULT_PRESERVED_OPTION_KEYS. There is no setting
+    /// for this in the launcher, it's only set by editing config.json and is read again on every sync
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "crate::try_deserialize")]
+    pub preserved_option_keys: Vec<Arc<str>>,
+    /// File names to leave out of synced folders on top of DEFAULT_SYNC_IGNORE_PATTERNS, `*` matches any characters
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "crate::try_deserialize")]
+    pub sync_ignore_patterns: Vec<Arc<str>>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub sync_conflict_policy: SyncConflictPolicy,
+    /// Days the .conflict copies kept while merging folders stay in the synced folder before tidying it up removes
+    /// them, 0 keeps them until they're removed by hand
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub sync_backup_retention_days: u32,
+}
+
+/// The parts of BackendConfig that describe the sync setup, so it can be copied to another machine
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct SyncConfigExport {
+    #[serde(default)]
+    pub sync_targets: SyncTargets,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sync_target_filters: BTreeMap<Arc<str>, Arc<str>>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub verified_sync_targets: BTreeSet<Arc<str>>,
+    #[serde(default, skip_serializing_if = "EnumSet::is_empty")]
+    pub synced_instance_settings: EnumSet<SyncedInstanceSetting>,
+    /// Missing in hand-written exports, which then leave the strategy and policy alone unless they replace everything
+    #[serde(default, skip_serializing_if = "crate::skip_if_none")]
+    pub link_strategy: Option<LinkStrategy>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none")]
+    pub sync_conflict_policy: Option<SyncConflictPolicy>,
+}
+
+impl SyncConfigExport {
+    pub fn from_config(config: &BackendConfig) -> Self {
+        Self {
+            sync_targets: config.sync_targets.clone(),
+            sync_target_filters: config.sync_target_filters.clone(),
+            verified_sync_targets: config.verified_sync_targets.clone(),
+            synced_instance_settings: config.synced_instance_settings,
+            link_strategy: Some(config.link_strategy),
+            sync_conflict_policy: Some(config.sync_conflict_policy),
+        }
+    }
+}
+
+/// How the instance copy to sync from is chosen when instances have different versions of a file
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyncConflictPolicy {
+    /// The most recently created or modified copy
+    #[default]
+    LatestMtime,
+    /// The version most instances agree on, with the newest copy breaking ties
+    MajorityHash,
+    LargestFile,
+}
+
+/// How folder sync targets are linked into instances
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkStrategy {
+    Symlink,
+    /// Only available on Windows, behaves like Symlink elsewhere
+    Junction,
+    /// Hardlinks every file individually, subdirectories are recreated as real folders
+    Hardlink,
+    Copy,
+}
+
+impl Default for LinkStrategy {
+    fn default() -> Self {
+        if cfg!(windows) {
+            Self::Junction
+        } else {
+            Self::Symlink
+        }
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -17,35 +130,159 @@ pub struct SyncTargets {
     pub folders: BTreeSet<Arc<str>>,
 }
 
+/// Instance settings that can be shared between all instances that have file syncing enabled.
+/// Memory is deliberately not included because it depends on the machine rather than the instance
+#[derive(Debug, EnumSetType)]
+pub enum SyncedInstanceSetting {
+    JvmFlags = 0,
+    JvmBinary = 1,
+    WrapperCommand = 2,
+    LinuxWrapper = 3,
+    SystemLibraries = 4,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct SyncedInstanceSettings {
+    #[serde(default, skip_serializing_if = "crate::skip_if_none", deserialize_with = "crate::try_deserialize")]
+    pub jvm_flags: Option<InstanceJvmFlagsConfiguration>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none", deserialize_with = "crate::try_deserialize")]
+    pub jvm_binary: Option<InstanceJvmBinaryConfiguration>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none", deserialize_with = "crate::try_deserialize")]
+    pub wrapper_command: Option<InstanceWrapperCommandConfiguration>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none", deserialize_with = "crate::try_deserialize")]
+    pub linux_wrapper: Option<InstanceLinuxWrapperConfiguration>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none", deserialize_with = "crate::try_deserialize")]
+    pub system_libraries: Option<InstanceSystemLibrariesConfiguration>,
+}
+
+impl SyncedInstanceSettings {
+    /// Copies the shared settings into `configuration`, seeding any that haven't been shared yet.
+    /// Returns true if the shared settings were modified
+    pub fn apply_to(&mut self, settings: EnumSet<SyncedInstanceSetting>, configuration: &mut InstanceConfiguration) -> bool {
+        let mut seeded = false;
+        for setting in settings {
+            seeded |= match setting {
+                SyncedInstanceSetting::JvmFlags => sync_setting(&mut self.jvm_flags, &mut configuration.jvm_flags),
+                SyncedInstanceSetting::JvmBinary => sync_setting(&mut self.jvm_binary, &mut configuration.jvm_binary),
+                SyncedInstanceSetting::WrapperCommand => sync_setting(&mut self.wrapper_command, &mut configuration.wrapper_command),
+                SyncedInstanceSetting::LinuxWrapper => sync_setting(&mut self.linux_wrapper, &mut configuration.linux_wrapper),
+                SyncedInstanceSetting::SystemLibraries => sync_setting(&mut self.system_libraries, &mut configuration.system_libraries),
+            };
+        }
+        seeded
+    }
+
+    pub fn update_from(&mut self, settings: EnumSet<SyncedInstanceSetting>, configuration: &InstanceConfiguration) {
+        for setting in settings {
+            match setting {
+                SyncedInstanceSetting::JvmFlags => self.jvm_flags = configuration.jvm_flags.clone(),
+                SyncedInstanceSetting::JvmBinary => self.jvm_binary = configuration.jvm_binary.clone(),
+                SyncedInstanceSetting::WrapperCommand => self.wrapper_command = configuration.wrapper_command.clone(),
+                SyncedInstanceSetting::LinuxWrapper => self.linux_wrapper = configuration.linux_wrapper,
+                SyncedInstanceSetting::SystemLibraries => self.system_libraries = configuration.system_libraries.clone(),
+            }
+        }
+    }
+}
+
+/// The contents of instance_settings.json in the synced folder. Instances in a sync group share the settings of
+/// that group, the ones without a group share the settings at the top level, where they were before groups existed
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct SharedInstanceSettings {
+    #[serde(flatten)]
+    pub ungrouped: SyncedInstanceSettings,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty", deserialize_with = "crate::try_deserialize")]
+    pub groups: BTreeMap<Arc<str>, SyncedInstanceSettings>,
+}
+
+impl SharedInstanceSettings {
+    pub fn group_mut(&mut self, group: Option<&str>) -> &mut SyncedInstanceSettings {
+        match group {
+            Some(group) => self.groups.entry(group.into()).or_default(),
+            None => &mut self.ungrouped,
+        }
+    }
+}
+
+fn sync_setting<T: Clone>(shared: &mut Option<T>, current: &mut Option<T>) -> bool {
+    if let Some(shared) = shared {
+        *current = Some(shared.clone());
+        false
+    } else if let Some(current) = current {
+        *shared = Some(current.clone());
+        true
+    } else {
+        false
+    }
+}
+
 fn is_default_sync_targets(sync_targets: &SyncTargets) -> bool {
     sync_targets.files.is_empty() && sync_targets.folders.is_empty()
 }
 
+/// Also reads the legacy bitset, so a config that is read without going through migrate_backend_config
+/// first, e.g. one changed on disk while the launcher is running, doesn't lose its targets
 fn try_deserialize_sync_targets<'de, D>(deserializer: D) -> Result<SyncTargets, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
     let value = serde_json::Value::deserialize(deserializer)?;
-
-    // Migration from previous bitset
     if let serde_json::Value::Number(_) = value {
-        let Ok(legacy) = EnumSet::<LegacySyncTarget>::deserialize(value) else {
-            return Ok(SyncTargets::default());
-        };
+        return Ok(legacy_sync_targets(value));
+    }
+    Ok(SyncTargets::deserialize(value).unwrap_or_default())
+}
 
-        let mut targets = SyncTargets::default();
-        for legacy_target in legacy {
-            let (name, file) = legacy_target.get_new_target();
-            if file {
-                targets.files.insert(name.into());
-            } else {
-                targets.folders.insert(name.into());
-            }
-        }
-        return Ok(targets);
+/// Upgrades the raw JSON of a config written by an older version of the launcher so that it deserializes
+/// as BACKEND_CONFIG_VERSION. Returns whether anything changed and the config should be saved again
+pub fn migrate_backend_config(value: &mut serde_json::Value) -> bool {
+    let Some(object) = value.as_object_mut() else {
+        return false;
+    };
+
+    let from_version = object.get("version").and_then(serde_json::Value::as_u64).unwrap_or(0);
+    if from_version >= BACKEND_CONFIG_VERSION as u64 {
+        return false;
     }
 
-    Ok(SyncTargets::deserialize(value).unwrap_or_default())
+    if from_version < 1 {
+        migrate_legacy_sync_targets(object);
+    }
+
+    object.insert("version".into(), BACKEND_CONFIG_VERSION.into());
+    true
+}
+
+/// Version 0 could store the sync targets as a bitset of LegacySyncTarget
+fn migrate_legacy_sync_targets(object: &mut serde_json::Map<String, serde_json::Value>) {
+    let Some(value @ serde_json::Value::Number(_)) = object.get("sync_targets") else {
+        return;
+    };
+
+    match serde_json::to_value(legacy_sync_targets(value.clone())) {
+        Ok(targets) => {
+            object.insert("sync_targets".into(), targets);
+        },
+        Err(_) => {
+            object.remove("sync_targets");
+        },
+    }
+}
+
+fn legacy_sync_targets(value: serde_json::Value) -> SyncTargets {
+    let mut targets = SyncTargets::default();
+    let Ok(legacy) = EnumSet::<LegacySyncTarget>::deserialize(value) else {
+        return targets;
+    };
+    for legacy_target in legacy {
+        let (name, file) = legacy_target.get_new_target();
+        if file {
+            targets.files.insert(name.into());
+        } else {
+            targets.folders.insert(name.into());
+        }
+    }
+    targets
 }
 
 #[derive(Debug, enum_map::Enum, EnumSetType, strum::EnumIter)]
@@ -88,3 +325,103 @@ impl LegacySyncTarget {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// options.txt, servers.dat and saves as the bitset written before sync targets could be any path
+    const LEGACY_CONFIG: &str = r#"{"sync_targets":11,"dont_open_game_output_when_launching":true}"#;
+
+    fn legacy_targets() -> SyncTargets {
+        SyncTargets {
+            files: ["options.txt", "servers.dat"].into_iter().map(Arc::from).collect(),
+            folders: ["saves"].into_iter().map(Arc::from).collect(),
+        }
+    }
+
+    fn assert_targets_eq(targets: &SyncTargets, expected: &SyncTargets) {
+        assert_eq!(targets.files, expected.files);
+        assert_eq!(targets.folders, expected.folders);
+    }
+
+    #[test]
+    fn sync_groups_share_their_own_settings() {
+        let flags = |flags: &str| InstanceJvmFlagsConfiguration { enabled: true, flags: flags.into() };
+        let settings = EnumSet::only(SyncedInstanceSetting::JvmFlags);
+
+        // Written before groups existed, these are the ungrouped settings
+        let mut shared: SharedInstanceSettings = serde_json::from_str(r#"{"jvm_flags":{"enabled":true,"flags":"-Xss4M"}}"#).unwrap();
+
+        let mut modded = InstanceConfiguration::new("1.21.1".into(), crate::loader::Loader::Fabric);
+        modded.jvm_flags = Some(flags("-XX:+UseZGC"));
+        assert!(shared.group_mut(Some("modded")).apply_to(settings, &mut modded));
+
+        let mut other_modded = InstanceConfiguration::new("1.21.1".into(), crate::loader::Loader::Fabric);
+        shared.group_mut(Some("modded")).apply_to(settings, &mut other_modded);
+        let mut vanilla = InstanceConfiguration::new("1.21.1".into(), crate::loader::Loader::Vanilla);
+        shared.group_mut(None).apply_to(settings, &mut vanilla);
+
+        assert_eq!(other_modded.jvm_flags.map(|jvm_flags| jvm_flags.flags), Some("-XX:+UseZGC".into()));
+        assert_eq!(vanilla.jvm_flags.map(|jvm_flags| jvm_flags.flags), Some("-Xss4M".into()));
+
+        let reloaded: SharedInstanceSettings = serde_json::from_str(&serde_json::to_string(&shared).unwrap()).unwrap();
+        assert_eq!(reloaded.ungrouped.jvm_flags.map(|jvm_flags| jvm_flags.flags), Some("-Xss4M".into()));
+        assert!(reloaded.groups.contains_key("modded"));
+    }
+
+    #[test]
+    fn sync_config_export_round_trips() {
+        let config = BackendConfig {
+            sync_targets: SyncTargets {
+                files: ["options.txt", "config/*.json"].into_iter().map(Arc::from).collect(),
+                folders: ["saves", "resourcepacks"].into_iter().map(Arc::from).collect(),
+            },
+            sync_target_filters: [("saves".into(), "loader:fabric".into())].into_iter().collect(),
+            verified_sync_targets: ["saves".into()].into_iter().collect(),
+            synced_instance_settings: SyncedInstanceSetting::JvmFlags | SyncedInstanceSetting::WrapperCommand,
+            link_strategy: LinkStrategy::Copy,
+            sync_conflict_policy: SyncConflictPolicy::MajorityHash,
+            ..Default::default()
+        };
+        let export = SyncConfigExport::from_config(&config);
+
+        let json = serde_json::to_string(&export).unwrap();
+        let imported: SyncConfigExport = serde_json::from_str(&json).unwrap();
+
+        assert_targets_eq(&imported.sync_targets, &export.sync_targets);
+        assert_eq!(imported.sync_target_filters, export.sync_target_filters);
+        assert_eq!(imported.verified_sync_targets, export.verified_sync_targets);
+        assert_eq!(imported.synced_instance_settings, export.synced_instance_settings);
+        assert_eq!(imported.link_strategy, Some(LinkStrategy::Copy));
+        assert_eq!(imported.sync_conflict_policy, Some(SyncConflictPolicy::MajorityHash));
+        assert_eq!(serde_json::to_string(&imported).unwrap(), json);
+    }
+
+    #[test]
+    fn legacy_config_is_upgraded_to_current_version() {
+        let mut value: serde_json::Value = serde_json::from_str(LEGACY_CONFIG).unwrap();
+        assert!(migrate_backend_config(&mut value));
+        assert!(!migrate_backend_config(&mut value));
+
+        let config: BackendConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(config.version, BACKEND_CONFIG_VERSION);
+        assert!(config.dont_open_game_output_when_launching);
+        assert_targets_eq(&config.sync_targets, &legacy_targets());
+
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["version"], BACKEND_CONFIG_VERSION);
+        assert!(saved["sync_targets"].is_object());
+
+        let reloaded: BackendConfig = serde_json::from_value(saved).unwrap();
+        assert_eq!(reloaded.version, BACKEND_CONFIG_VERSION);
+        assert_targets_eq(&reloaded.sync_targets, &legacy_targets());
+    }
+
+    #[test]
+    fn legacy_targets_survive_loading_without_migrating() {
+        let config: BackendConfig = serde_json::from_str(LEGACY_CONFIG).unwrap();
+        assert_eq!(config.version, 0);
+        assert_targets_eq(&config.sync_targets, &legacy_targets());
+    }
+}
@@ -1,4 +1,4 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::BTreeSet, path::Path, sync::Arc};
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -28,6 +28,13 @@ pub struct InstanceConfiguration {
     pub instance_fallback_icon: Option<Ustr>,
     #[serde(default, deserialize_with = "crate::try_deserialize")]
     pub disable_file_syncing: bool,
+    /// Sync targets this instance opted out of, by name since a name is only synced as either a file or a folder
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "BTreeSet::is_empty")]
+    pub sync_exclusions: BTreeSet<Arc<str>>,
+    /// Instances in the same sync group share their synced instance settings, and sync target filters can pick
+    /// out a group with `group:<name>`
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub sync_group: Option<Arc<str>>,
 }
 
 impl InstanceConfiguration {
@@ -44,6 +51,8 @@ impl InstanceConfiguration {
             system_libraries: None,
             instance_fallback_icon: None,
             disable_file_syncing: false,
+            sync_exclusions: BTreeSet::new(),
+            sync_group: None,
         }
     }
 }